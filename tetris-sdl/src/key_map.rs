@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+
+use sdl2::keyboard::Keycode;
+use tetris::Action;
+
+pub struct KeyMap {
+    map: HashMap<Keycode, Action>,
+}
+
+impl KeyMap {
+    pub fn new() -> Self {
+        let mut map = HashMap::new();
+        map.insert(Keycode::A, Action::LEFT);
+        map.insert(Keycode::D, Action::RIGHT);
+        map.insert(Keycode::S, Action::DOWN);
+        map.insert(Keycode::Space, Action::DROP);
+        map.insert(Keycode::L, Action::ROTATE_C);
+        map.insert(Keycode::J, Action::ROTATE_A);
+        map.insert(Keycode::K, Action::ROTATE_H);
+        map.insert(Keycode::LShift, Action::HOLD);
+        // only with --undo
+        map.insert(Keycode::Z, Action::UNDO);
+        map.insert(Keycode::Y, Action::REDO);
+
+
+        Self { map }
+    }
+
+    pub fn get(&self, key: &Keycode) -> Option<&Action> {
+        self.map.get(key)
+    }
+}
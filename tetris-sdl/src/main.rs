@@ -0,0 +1,667 @@
+mod key_map;
+mod modes;
+mod playback;
+
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use key_map::KeyMap;
+use tetris::fumen;
+use tetris::gravity::GravityCurve;
+use tetris::history::History;
+use tetris::mode::{Mode, Sprint, SPRINT_LINES};
+use tetris::randomizer::RandomizerKind;
+use tetris::records::Records;
+use tetris::replay::{Replay, ReplayEvent};
+use tetris::save::SavedGame;
+use tetris::rotation::RotationSystemKind;
+use tetris::tetris_engine::*;
+use tetris::user_controls::*;
+
+use sdl2::gfx::primitives::DrawRenderer;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use sdl2::rect::Rect;
+use sdl2::ttf::Font;
+
+// simulation rate, in frames per second
+const FPS : f64 = 60.0;
+const FRAME_MS : f64 = 1000.0 / FPS;
+// behind by more than this the simulation skips ahead
+const MAX_LAG_MS : f64 = 250.0;
+
+const REPLAYS_DIR : &str = "replays";
+const SAVE_FILE : &str = "tetris.save";
+const RECORDS_FILE : &str = "tetris.records";
+
+const SQUARE :u32 = 30;
+const PADDING : u32 = 20;
+
+// the board area, squares shrink when the board doesn't fit in it
+const MAX_BOARD_WIDTH : u32 = 600;
+const MAX_BOARD_HEIGHT : u32 = 600;
+
+const LEFT_AREA_WIDTH : u32 = 150;
+const RIGHT_AREA_WIDTH : u32 = 150;
+
+const HOLD_SQUARE_SIZE : u32 = 20;
+const HOLD_WIDTH: u32 = HOLD_SQUARE_SIZE * 4;
+const HOLD_HEIGHT: u32 = HOLD_SQUARE_SIZE * 3;
+const HOLD_MARGIN: u32 = 20;
+const HOLD_PADDING: u32 = 10;
+
+const NEXTPIECE_HEIGHT : u32 = HOLD_SQUARE_SIZE * 15;
+
+const GHOST_COLOR : Color = Color::RGBA(255,255,255, 128);
+
+// hidden rows drawn above the board, where pieces spawn
+const BUFFER_ROWS_SHOWN : u32 = 2;
+// the top hidden row that is drawn
+const FIRST_SHOWN_ROW : i16 = BUFFER_HEIGHT as i16 - BUFFER_ROWS_SHOWN as i16;
+
+const GARBAGE_METER_WIDTH : u32 = 8;
+const GARBAGE_METER_COLOR : Color = Color::RGB(255, 40, 40);
+
+// window sizes that depend on the board dimensions
+struct Layout {
+    square : u32,
+    board_width : u32,
+    board_height : u32,
+    // y of the board, below the room for the shown hidden rows
+    board_top : u32,
+    width : u32,
+    height : u32,
+}
+
+impl Layout {
+    fn new(columns : u32, rows : u32) -> Self{
+        let square = SQUARE.min(MAX_BOARD_WIDTH / columns).min(MAX_BOARD_HEIGHT / rows);
+        let board_width = square * columns;
+        let board_height = square * rows;
+        Self{
+            square,
+            board_width,
+            board_height,
+            board_top : PADDING + square * BUFFER_ROWS_SHOWN,
+            width : LEFT_AREA_WIDTH + board_width + 2 * PADDING + RIGHT_AREA_WIDTH,
+            height : MAX_BOARD_HEIGHT + 2 * PADDING + square * BUFFER_ROWS_SHOWN,
+        }
+    }
+}
+
+
+#[allow(non_snake_case)]
+fn main() {
+    // --seed N replays the same piece sequence on every restart
+    let args : Vec<String> = std::env::args().collect();
+    let fixed_seed = arg_value(&args, "--seed")
+        .map(|seed| seed.parse::<u64>().expect("--seed must be a number"));
+
+    let mut rules = Rules::new();
+    if let Some(name) = arg_value(&args, "--randomizer"){
+        rules.randomizer = RandomizerKind::from_name(name)
+            .expect("--randomizer must be one of 7bag, 14bag, random, nes, tgm");
+    }
+    if let Some(sequence) = arg_value(&args, "--sequence"){
+        rules.randomizer = RandomizerKind::from_name(&format!("fixed {}", sequence))
+            .expect("--sequence must be one or more of IJLOSTZ");
+    }
+    if let Some(name) = arg_value(&args, "--rotation"){
+        rules.rotation_system = RotationSystemKind::from_name(name)
+            .expect("--rotation must be one of srs, srs+, ars, nrs");
+    }
+    if let Some(name) = arg_value(&args, "--gravity"){
+        rules.gravity = GravityCurve::from_name(name)
+            .expect("--gravity must be one of guideline, nes, tgm, 20g or rows per frame");
+    }
+    if let Some(width) = arg_value(&args, "--width"){
+        rules.width = width.parse().expect("--width must be a number");
+    }
+    if let Some(height) = arg_value(&args, "--height"){
+        rules.height = height.parse().expect("--height must be a number");
+    }
+    if let Err(error) = rules.validate(){
+        panic!("--width and --height: {}", error);
+    }
+    if let Some(mode) = arg_value(&args, "--lock-reset"){
+        rules.lock_reset = LockReset::from_name(mode)
+            .expect("--lock-reset must be one of move, infinite, step");
+    }
+    for (arg, delay) in [("--are", &mut rules.are), ("--line-are", &mut rules.line_are), ("--line-clear-delay", &mut rules.line_clear_delay)]{
+        if let Some(frames) = arg_value(&args, arg){
+            *delay = frames.parse().expect("delays must be a number of frames");
+        }
+    }
+    // --undo N is practice mode, the last N placements can be undone
+    if let Some(placements) = arg_value(&args, "--undo"){
+        rules.undo_limit = placements.parse().expect("--undo must be a number of placements");
+    }
+    let mut handling = Handling::new();
+    if let Some(frames) = arg_value(&args, "--das"){
+        handling.das_delay = frames.parse().expect("--das must be a number of frames");
+    }
+    if let Some(frames) = arg_value(&args, "--arr"){
+        handling.arr = frames.parse().expect("--arr must be a number of frames");
+    }
+    handling.irs = args.iter().any(|arg| arg == "--irs");
+    handling.ihs = args.iter().any(|arg| arg == "--ihs");
+    if let Some(name) = arg_value(&args, "--sdf"){
+        handling.sdf = SoftDropFactor::from_name(name).expect("--sdf must be a number or inf");
+    }
+    handling.das_carry = !args.iter().any(|arg| arg == "--no-das-carry");
+    if let Some(frames) = arg_value(&args, "--dcd"){
+        handling.dcd = frames.parse().expect("--dcd must be a number of frames");
+    }
+    if let Some(name) = arg_value(&args, "--direction"){
+        handling.direction_policy = DirectionPolicy::from_name(name).expect("--direction must be one of last, cancel");
+    }
+    // --sprint LINES races to clear that many lines
+    if let Some(lines) = arg_value(&args, "--sprint"){
+        rules.mode = lines.parse().ok()
+            .filter(|lines| SPRINT_LINES.contains(lines))
+            .map(Mode::SPRINT)
+            .expect("--sprint must be one of 20, 40, 100, 1000");
+    }
+    // --blitz and --ultra are score attacks of 2 and 3 minutes
+    if args.iter().any(|arg| arg == "--blitz"){
+        rules.mode = Mode::BLITZ;
+    }
+    if args.iter().any(|arg| arg == "--ultra"){
+        rules.mode = Mode::ULTRA;
+    }
+    // --replay FILE plays a recorded game instead, with its own rules
+    let replay = arg_value(&args, "--replay")
+        .map(|path| Replay::load(path).unwrap_or_else(|error| panic!("can't load {}: {}", path, error)));
+    if let Some(replay) = &replay{
+        rules = replay.rules.clone();
+    }
+    // F5 saves the game in progress to --save FILE, F9 loads it back, --resume starts from it
+    let save_path = arg_value(&args, "--save").map_or(SAVE_FILE.to_string(), |path| path.clone());
+    let mut resume = if args.iter().any(|arg| arg == "--resume") {
+        Some(SavedGame::load(&save_path).unwrap_or_else(|error| panic!("can't load {}: {}", save_path, error)))
+    } else {
+        None
+    };
+    if let Some(saved) = &resume{
+        rules = saved.game.rules().clone();
+    }
+    // --fumen STRING starts every game from a fumen diagram, C copies the board as one, V loads one
+    let start_fumen = arg_value(&args, "--fumen").cloned();
+    if let Some(text) = &start_fumen{
+        fumen::decode(text)
+            .and_then(|pages| fumen::load_into(&mut Game::new(rules.clone(), 0), &pages))
+            .unwrap_or_else(|error| panic!("can't load the fumen: {}", error));
+    }
+    let mut records = Records::load(RECORDS_FILE).unwrap_or_else(|error| {
+        println!("can't load {}: {}", RECORDS_FILE, error);
+        Records::new()
+    });
+    let layout = Layout::new(rules.width as u32, rules.height as u32);
+
+    let sdl_context = sdl2::init().unwrap();
+    let video = sdl_context.video().unwrap();
+    let window = video.window("Tetris", layout.width, layout.height)
+        .position_centered()
+        .build()
+        .unwrap();
+    let font_path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/DejaVuSans.ttf");
+    let ttf_context = sdl2::ttf::init().unwrap();
+    let mut canvas = window.into_canvas().present_vsync().build().unwrap();
+    let mut events = sdl_context.event_pump().unwrap();
+    let timer = sdl_context.timer().unwrap();
+    let font = ttf_context.load_font(font_path, 24).unwrap();
+    let small_font = ttf_context.load_font(font_path, 14).unwrap();
+    let key_map = KeyMap::new();
+
+    if let Some(replay) = replay{
+        playback::play(&mut canvas, &mut events, &timer, &layout, &font, &small_font, replay);
+        return;
+    }
+
+    loop{
+        let (mut frames, mut game, mut userControl, mut replay) = match resume.take() {
+            // only games played from the start can be replayed
+            Some(saved) => (saved.game.frames(), saved.game, saved.controls, None),
+            None => {
+                let seed = fixed_seed.unwrap_or_else(|| rand::random::<u32>() as u64);
+                let mut replay = Replay::new(seed, rules.clone(), handling.clone());
+                replay.fumen = start_fumen.clone();
+                (0, replay.start().unwrap(), UserControl::new(handling.clone()), Some(replay))
+            },
+        };
+        // a resumed game keeps the undo limit it was saved with
+        let mut history = History::new(game.rules().undo_limit);
+        history.start(&game);
+        let mut sprint = new_sprint(&game);
+        // sprints and timed games wait for a ready-go countdown before their first frame
+        let mut countdown = if game.rules().mode != Mode::MARATHON && frames == 0 {modes::COUNTDOWN_FRAMES} else {0};
+
+        let mut last_lock : Option<LockResult> = None;
+        // the simulation runs at a fixed 60 Hz whatever the render rate is
+        let mut clock = timer.ticks() as f64;
+        // inputs with their SDL timestamp, waiting for their frame
+        let mut inputs : VecDeque<(u32, ReplayEvent)> = VecDeque::new();
+        
+        let stop = 'running : loop  {
+            for event in events.poll_iter() {
+                match event {
+                    Event::Quit {..} | 
+                    Event::KeyDown { keycode: Some(Keycode::Escape), .. } => break 'running true,
+                    Event::KeyDown { keycode: Some(Keycode::R), .. } => break 'running false,
+                    Event::KeyDown { keycode: Some(Keycode::F5), .. } => {
+                        if let Err(error) = SavedGame::save(&game, &userControl, &save_path){
+                            println!("can't save the game: {}", error);
+                        }
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::F9), .. } => {
+                        match SavedGame::load(&save_path) {
+                            // the window is sized for the board it was opened with
+                            Ok(saved) if saved.game.width() != game.width() || saved.game.visible_height() != game.visible_height() => {
+                                println!("can't load {}: the board size is different", save_path);
+                            },
+                            Ok(saved) => {
+                                (frames, game, userControl) = (saved.game.frames(), saved.game, saved.controls);
+                                replay = None;
+                                history = History::new(game.rules().undo_limit);
+                                history.start(&game);
+                                sprint = new_sprint(&game);
+                                countdown = 0;
+                                inputs.clear();
+                                last_lock = None;
+                            },
+                            Err(error) => println!("can't load {}: {}", save_path, error),
+                        }
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::C), .. } => {
+                        if let Err(error) = fumen::from_game(&game).and_then(|text| video.clipboard().set_clipboard_text(&text)){
+                            println!("can't copy the board: {}", error);
+                        }
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::V), .. } => {
+                        let pages = video.clipboard().clipboard_text().and_then(|text| fumen::decode(&text));
+                        match pages.and_then(|pages| fumen::load_into(&mut game, &pages)) {
+                            // a new position, the score and the clock start over
+                            Ok(()) => {
+                                frames = 0;
+                                replay = None;
+                                history.start(&game);
+                                sprint = new_sprint(&game);
+                                inputs.clear();
+                                last_lock = None;
+                            },
+                            Err(error) => println!("can't load the fumen: {}", error),
+                        }
+                    },
+                    Event::KeyDown { keycode: Some(Keycode::G), timestamp, .. } => inputs.push_back((timestamp, ReplayEvent::GARBAGE(4))),
+
+                    Event::KeyDown { keycode: Some(key), repeat: false, timestamp, .. } => {
+                        if let Some(action) = key_map.get(&key){
+                            inputs.push_back((timestamp, ReplayEvent::ACTION(*action, true)));
+                        }
+                    },
+                    Event::KeyUp { keycode: Some(key), timestamp, .. } => {
+                        if let Some(action) = key_map.get(&key){
+                            inputs.push_back((timestamp, ReplayEvent::ACTION(*action, false)));
+                        }
+                    },
+                    _ => {}
+                }
+            }
+
+            // after a stall (window dragged, machine busy) skip the lost time instead of fast forwarding
+            let now = timer.ticks() as f64;
+            if now - clock > MAX_LAG_MS{
+                clock = now - FRAME_MS;
+            }
+            while clock + FRAME_MS <= now{
+                clock += FRAME_MS;
+                // keys pressed during the countdown are applied on the first frame
+                if countdown > 0{
+                    countdown -= 1;
+                    continue;
+                }
+                // inputs are applied at the start of the frame they happened in, in order,
+                // where in the frame doesn't matter
+                while let Some(&(timestamp, event)) = inputs.front(){
+                    if timestamp as f64 > clock || game.top_out().is_some(){
+                        break;
+                    }
+                    inputs.pop_front();
+                    if let Some(replay) = &mut replay{
+                        replay.record(frames, event);
+                    }
+                    if let Some(result) = event.apply(&mut game, &mut userControl, &mut history){
+                        last_lock = Some(result);
+                    }
+                }
+                if let Some(result) = userControl.update(&mut game){
+                    history.locked(&game);
+                    last_lock = Some(result);
+                }
+                // the frame that tops out is counted, so its replay plays it too
+                frames += 1;
+                if game.top_out().is_some() || game.time_up(){
+                    break 'running false;
+                }
+                if let Some(sprint) = &mut sprint{
+                    sprint.update(&game, frames);
+                    if sprint.finish.is_some(){
+                        break 'running false;
+                    }
+                }
+            }
+
+            draw_game(&mut canvas, &layout, &game, &last_lock, &font, &small_font, frames);
+            if let Some(sprint) = &sprint{
+                modes::draw_sprint(&mut canvas, sprint, records.best_sprint(sprint.lines), game.lines_cleared, frames, &font, &small_font);
+            }
+            if game.time_left().is_some(){
+                modes::draw_timed(&mut canvas, &game, records.best_score(game.rules().mode), &font, &small_font);
+            }
+            if countdown > 0{
+                modes::draw_countdown(&mut canvas, &layout, &font, countdown);
+            }
+            canvas.present();
+            // vsync paces rendering, this only keeps the loop from spinning without it
+            std::thread::sleep(Duration::from_millis(1));
+        };
+
+        let mode = game.rules().mode;
+        let finished = sprint.filter(|sprint| sprint.finish.is_some());
+        let best = finished.as_ref().and_then(|sprint| records.best_sprint(sprint.lines).cloned());
+        let best_score = records.best_score(mode);
+        // only games played from the start with the standard rules can be records
+        let record = replay.as_ref().is_some_and(|replay| replay.counts_for_records()) && match &finished {
+            Some(sprint) => records.add_sprint(sprint),
+            None => game.time_up() && records.add_score(mode, game.score),
+        };
+        let saved = if record {records.save(RECORDS_FILE)} else {Ok(())};
+        if let Err(error) = saved{
+            println!("can't save {}: {}", RECORDS_FILE, error);
+        }
+        if let Some(mut replay) = replay{
+            replay.frames = frames;
+            save_replay(&replay);
+        }
+
+        let stop = if let Some(sprint) = &finished {
+            modes::result_screen(&mut events, || {
+                draw_game(&mut canvas, &layout, &game, &last_lock, &font, &small_font, frames);
+                modes::draw_sprint_results(&mut canvas, &layout, &font, &small_font, sprint, best.as_ref(), record, game.seed);
+                canvas.present();
+            })
+        } else if game.time_up() {
+            modes::result_screen(&mut events, || {
+                draw_game(&mut canvas, &layout, &game, &last_lock, &font, &small_font, frames);
+                modes::draw_timed_results(&mut canvas, &layout, &font, &small_font, &game, best_score, record);
+                canvas.present();
+            })
+        } else if game.top_out().is_some() {
+            modes::result_screen(&mut events, || {
+                draw_game(&mut canvas, &layout, &game, &last_lock, &font, &small_font, frames);
+                modes::draw_game_over(&mut canvas, &layout, &font, &small_font, &game);
+                canvas.present();
+            })
+        } else {
+            stop
+        };
+        if stop{
+            break;
+        }
+    }
+}
+
+
+
+fn new_sprint(game : &Game) -> Option<Sprint>{
+    match game.rules().mode {
+        Mode::SPRINT(lines) => Some(Sprint::new(lines)),
+        Mode::MARATHON | Mode::BLITZ | Mode::ULTRA => None,
+    }
+}
+
+// every game is saved in replays/, named after when it ended
+fn save_replay(replay : &Replay){
+    let time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0);
+    let path = format!("{}/{}-{}.replay", REPLAYS_DIR, time, replay.seed);
+    if let Err(error) = std::fs::create_dir_all(REPLAYS_DIR).and_then(|_| replay.save(&path)){
+        println!("can't save the replay: {}", error);
+    }
+}
+
+fn draw_game(canvas: &mut Canvas<Window>, layout : &Layout, game : &Game, last_lock : &Option<LockResult>, font : &Font<'_, 'static>, small_font : &Font<'_, 'static>, frames : u32){
+    canvas.set_draw_color(Color::RGB(100, 100, 100));
+    canvas.clear();
+
+    renderBoard(canvas, layout, game, (0, 0));
+    drawHold(canvas, game);
+    drawNexts(canvas, layout, game);
+    drawLines(canvas, layout, game, font);
+    draw_lock_result(canvas, layout, last_lock, font);
+    draw_attack(canvas, layout, game, font, Duration::from_secs_f64(frames as f64 / FPS));
+    draw_text(canvas, small_font, &format!("SEED {}", game.seed), (HOLD_MARGIN as i32, layout.height as i32 - 30), Color::WHITE);
+}
+
+fn arg_value<'a>(args : &'a [String], name : &str) -> Option<&'a String>{
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|i| args.get(i + 1))
+}
+
+fn draw_square(canvas: &mut Canvas<Window>, (x, y) : (i16, i16), color: Color, square : i16, position : (i16, i16)) {
+    let offset : i16 = 1;
+    let sq : i16 = square - offset*2;
+    let corner : i16 = 3;
+    let mut vx = [corner, sq - corner, sq, sq, sq - corner, corner, 0, 0];
+    let mut vy= [0, 0, corner, sq - corner, sq, sq, sq-corner, corner];
+
+    for i in 0..8{
+        vx[i] += position.0 + square*x+offset;
+        vy[i] += position.1 + square*y+offset;
+    }
+
+    let _ = canvas.filled_polygon(&vx, &vy, color);
+
+    //canvas.set_draw_color(Color::RGB(color.r, color.g, color.b));
+    //canvas.fill_rect(Rect::new(x * (SQUARE as i32) + PADDING as i32, y * (SQUARE as i32) + PADDING as i32, SQUARE, SQUARE)).unwrap();
+    
+}
+
+#[allow(non_snake_case)]
+fn renderPiece(canvas: &mut Canvas<Window>, (pos_x,pos_y) : (i8, i8), tetromino : &Tetromino, color : Color, square : u32, position : (i16, i16)){
+    for (x, y) in tetromino{
+        let pos  = ((x + pos_x) as i16, (pos_y - y) as i16);
+        if pos.1 < FIRST_SHOWN_ROW{
+            continue;
+        }
+        draw_square(canvas, pos, color, square as i16, position);
+    }
+}
+
+
+
+#[allow(non_snake_case)]
+fn renderBoard(canvas: &mut Canvas<Window>, layout : &Layout, game : &Game, offset : (i32, i32)){
+    let rect = Rect::new(
+        (PADDING + LEFT_AREA_WIDTH) as i32 + offset.0, 
+        layout.board_top as i32 + offset.1, 
+        layout.board_width, layout.board_height
+    );
+    canvas.set_draw_color(Color::RGB(255, 255, 255));
+    let _ = canvas.draw_rect(rect);
+
+    // row 0 of the matrix is the top of the hidden buffer
+    let position = (rect.x as i16, rect.y as i16 - (BUFFER_HEIGHT as u32 * layout.square) as i16);
+
+    for y in FIRST_SHOWN_ROW as usize..game.board.len(){
+        for x in 0..game.board[y].len(){
+            if game.board[y][x] != 0 {
+                let color = getColor(game.board[y][x]);
+                draw_square(
+                    canvas, 
+                    (x as i16, y as i16), 
+                    color, 
+                    layout.square as i16, 
+                    position
+                );
+            }
+        }
+    }
+    let mut garbage_y = rect.y + rect.height() as i32;
+    for batch in game.garbage.batches(){
+        let height = ((batch.lines * layout.square) as i32).min(garbage_y - rect.y);
+        if height <= 0{
+            break;
+        }
+        garbage_y -= height;
+        canvas.set_draw_color(GARBAGE_METER_COLOR);
+        let _ = canvas.fill_rect(Rect::new(rect.x - GARBAGE_METER_WIDTH as i32 - 2, garbage_y, GARBAGE_METER_WIDTH, height as u32 - 1));
+    }
+
+    // full rows flash white until the line clear delay is over
+    for &y in game.clearing_rows().iter().filter(|&&y| y as i16 >= FIRST_SHOWN_ROW){
+        for x in 0..game.board[y].len(){
+            draw_square(canvas, (x as i16, y as i16), Color::WHITE, layout.square as i16, position);
+        }
+    }
+
+    if !game.piece_active(){
+        return;
+    }
+    let tetromino = &game.current_tetromino();
+
+    renderPiece(canvas, game.get_ghost(), tetromino, GHOST_COLOR, layout.square, position);
+    renderPiece(canvas, game.current_position, tetromino, getColor(game.current_piece.index), layout.square, position);    
+}
+
+fn draw_perfect_clear(canvas: &mut Canvas<Window>, layout : &Layout, font : &Font<'_, 'static>){
+    let x = (PADDING + LEFT_AREA_WIDTH + layout.board_width / 2) as i32;
+    let y = (layout.board_top + layout.board_height / 2) as i32;
+    draw_text(canvas, font, "PERFECT", (x - 55, y - 30), Color::RGB(255, 215, 0));
+    draw_text(canvas, font, "CLEAR", (x - 40, y), Color::RGB(255, 215, 0));
+}
+
+#[allow(non_snake_case)]
+fn getColor(square : u8) -> Color{
+    match square {
+        1 => Color::RGB(0, 255, 255),
+        2 => Color::RGB(255, 255, 0),
+        3 => Color::RGB(128, 0, 128),
+        4 => Color::RGB(0, 255, 0),
+        5 => Color::RGB(255, 0, 0),
+        6 => Color::RGB(0, 0, 255),
+        7 => Color::RGB(255, 165, 0),
+        GARBAGE => Color::RGB(150, 150, 150),
+        _ => Color::RGB(255, 255, 255),
+    }
+}
+
+
+#[allow(non_snake_case)]
+fn drawHold(canvas: &mut Canvas<Window>, game : &Game){
+    let rect = Rect::new(
+        (LEFT_AREA_WIDTH-HOLD_WIDTH-HOLD_MARGIN-HOLD_PADDING) as i32, 
+        (HOLD_MARGIN-HOLD_PADDING) as i32, 
+        HOLD_WIDTH + 2*HOLD_PADDING, HOLD_HEIGHT + 2*HOLD_PADDING);
+    
+    if let Some(piece) = game.hold_piece{
+        let tetromino = game.piece_tetromino(piece);
+        let color = getColor(piece.index);
+
+        let pos  = ((LEFT_AREA_WIDTH-HOLD_WIDTH-HOLD_MARGIN) as i16, HOLD_MARGIN as i16);
+        for (x, y) in tetromino{
+            draw_square(canvas, (1 + x as i16, 1 - y as i16), color, HOLD_SQUARE_SIZE as i16, pos);
+        }
+    }
+
+
+    canvas.set_draw_color(Color::WHITE);
+    let _ = canvas.draw_rect(rect);
+}
+
+#[allow(non_snake_case)]
+fn drawNexts(canvas: &mut Canvas<Window>, layout : &Layout, game : &Game){
+    let rect = Rect::new(
+        (layout.width-RIGHT_AREA_WIDTH + HOLD_MARGIN - HOLD_PADDING) as i32, 
+        (HOLD_MARGIN - HOLD_PADDING) as i32, 
+        HOLD_WIDTH + 2*HOLD_PADDING, NEXTPIECE_HEIGHT + 2*HOLD_PADDING);
+    
+    
+    let pos  = ((layout.width-RIGHT_AREA_WIDTH + HOLD_MARGIN) as i16, HOLD_MARGIN as i16);
+    
+    for (i, piece) in game.get_nexts().iter().enumerate().take(5) {
+        let tetromino = game.piece_tetromino(piece);
+        let color = getColor(piece.index);
+        for (x, y) in tetromino{
+            draw_square(canvas, (1 + x as i16, 1 + (i as i16)*3 - y as i16), color, HOLD_SQUARE_SIZE as i16, pos);
+        }
+    }
+
+    canvas.set_draw_color(Color::WHITE);
+    let _ = canvas.draw_rect(rect);
+}
+
+fn draw_text(canvas: &mut Canvas<Window>, font : &Font<'_, 'static>, text : &str, (x, y): (i32, i32), color : Color){
+    let surface = font
+        .render(text)
+        .blended(color)
+        .unwrap();
+
+    let texture_creator = canvas.texture_creator();
+    let texture = texture_creator
+        .create_texture_from_surface(&surface)
+        .unwrap();
+
+    let target = Rect::new(x, y, surface.width(), surface.height());
+
+    canvas.copy(&texture, None, Some(target)).unwrap();
+}
+
+#[allow(non_snake_case)]
+fn drawLines(canvas: &mut Canvas<Window>, layout : &Layout, game : &Game, font : &Font<'_, 'static>){
+
+    draw_text(canvas, font, "SCORE", ((layout.width - RIGHT_AREA_WIDTH )as i32, layout.height as i32 - 200), Color::WHITE);
+    draw_text(canvas, font, &game.score.to_string(), ((layout.width - RIGHT_AREA_WIDTH )as i32, layout.height as i32 - 175), Color::WHITE);
+
+    draw_text(canvas, font, "LINES", ((layout.width - RIGHT_AREA_WIDTH )as i32, layout.height as i32 - 100), Color::WHITE);
+    draw_text(canvas, font, &game.lines_cleared.to_string(), ((layout.width - RIGHT_AREA_WIDTH )as i32, layout.height as i32 - 75), Color::WHITE);
+
+    if game.combo > 0{
+        draw_text(canvas, font, &format!("COMBO {}", game.combo), (HOLD_MARGIN as i32, layout.height as i32 - 125), Color::WHITE);
+    }
+    if game.b2b > 0{
+        draw_text(canvas, font, &format!("B2B x{}", game.b2b), (HOLD_MARGIN as i32, layout.height as i32 - 100), Color::WHITE);
+    }
+}
+
+fn draw_attack(canvas: &mut Canvas<Window>, layout : &Layout, game : &Game, font : &Font<'_, 'static>, elapsed : Duration){
+    let minutes = elapsed.as_secs_f64() / 60.0;
+    let apm = if minutes > 0.0 {game.lines_sent as f64 / minutes} else {0.0};
+
+    draw_text(canvas, font, "SENT", (HOLD_MARGIN as i32, layout.height as i32 - 350), Color::WHITE);
+    draw_text(canvas, font, &game.lines_sent.to_string(), (HOLD_MARGIN as i32, layout.height as i32 - 325), Color::WHITE);
+    draw_text(canvas, font, "APM", (HOLD_MARGIN as i32, layout.height as i32 - 290), Color::WHITE);
+    draw_text(canvas, font, &format!("{:.1}", apm), (HOLD_MARGIN as i32, layout.height as i32 - 265), Color::WHITE);
+}
+
+fn draw_lock_result(canvas: &mut Canvas<Window>, layout : &Layout, last_lock : &Option<LockResult>, font : &Font<'_, 'static>){
+    let result = match last_lock {
+        Some(result) => result,
+        None => return,
+    };
+    if result.spin != SpinType::NONE{
+        draw_text(canvas, font, "T-SPIN", (HOLD_MARGIN as i32, layout.height as i32 - 200), Color::WHITE);
+    }
+    if result.spin == SpinType::MINI{
+        draw_text(canvas, font, "MINI", (HOLD_MARGIN as i32, layout.height as i32 - 175), Color::WHITE);
+    }
+    if result.perfect_clear{
+        draw_perfect_clear(canvas, layout, font);
+    }
+}
\ No newline at end of file
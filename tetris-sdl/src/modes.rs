@@ -0,0 +1,168 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::ttf::Font;
+use sdl2::video::Window;
+use sdl2::EventPump;
+use std::time::Duration;
+use tetris::mode::{format_delta, format_time, Sprint, SPLIT_LINES};
+use tetris::tetris_engine::Game;
+
+use crate::{draw_text, Layout, FPS, HOLD_MARGIN, LEFT_AREA_WIDTH, PADDING};
+
+/// READY for a second, then GO for a second, then the game starts
+pub const COUNTDOWN_FRAMES : u32 = 2 * FPS as u32;
+
+const AHEAD_COLOR : Color = Color::RGB(80, 255, 80);
+const BEHIND_COLOR : Color = Color::RGB(255, 80, 80);
+// splits listed on the result screen, the last ones when there are more
+const RESULT_SPLITS : usize = 20;
+// the timer turns red for the last seconds of a timed game
+const HURRY_FRAMES : u32 = 10 * FPS as u32;
+
+pub fn draw_countdown(canvas : &mut Canvas<Window>, layout : &Layout, font : &Font<'_, 'static>, countdown : u32){
+    let text = if countdown > FPS as u32 {"READY"} else {"GO"};
+    let (width, _) = font.size_of(text).unwrap_or((0, 0));
+    let x = (PADDING + LEFT_AREA_WIDTH + layout.board_width / 2) as i32 - width as i32 / 2;
+    let y = (layout.board_top + layout.board_height / 2) as i32 - 15;
+    draw_text(canvas, font, text, (x, y), Color::RGB(255, 215, 0));
+}
+
+/// time, lines left and the last split with how it compares to the best one
+pub fn draw_sprint(canvas : &mut Canvas<Window>, sprint : &Sprint, best : Option<&Sprint>, lines : u32, frames : u32, font : &Font<'_, 'static>, small_font : &Font<'_, 'static>){
+    let x = HOLD_MARGIN as i32;
+    draw_text(canvas, font, "TIME", (x, 110), Color::WHITE);
+    draw_text(canvas, font, &format_time(sprint.finish.unwrap_or(frames)), (x, 135), Color::WHITE);
+    draw_text(canvas, small_font, &format!("{} / {} LINES", lines.min(sprint.lines), sprint.lines), (x, 165), Color::WHITE);
+    if let Some((i, split)) = sprint.splits.iter().enumerate().next_back(){
+        let best_split = best.and_then(|best| best.splits.get(i));
+        draw_split(canvas, small_font, i, *split, best_split.copied(), (x, 190));
+    }
+}
+
+fn draw_split(canvas : &mut Canvas<Window>, font : &Font<'_, 'static>, i : usize, split : u32, best : Option<u32>, (x, y) : (i32, i32)){
+    let text = format!("{} {}", (i as u32 + 1) * SPLIT_LINES, format_time(split));
+    draw_text(canvas, font, &text, (x, y), Color::WHITE);
+    if let Some(best) = best{
+        let color = if split <= best {AHEAD_COLOR} else {BEHIND_COLOR};
+        draw_text(canvas, font, &format_delta(split, best), (x + 80, y), color);
+    }
+}
+
+/// the finished sprint over the board, with its splits against the best sprint before it,
+/// `record` is true when it was kept as the new best
+#[allow(clippy::too_many_arguments)]
+pub fn draw_sprint_results(canvas : &mut Canvas<Window>, layout : &Layout, font : &Font<'_, 'static>, small_font : &Font<'_, 'static>, sprint : &Sprint, best : Option<&Sprint>, record : bool, seed : u64){
+    let finish = sprint.finish.unwrap_or(0);
+    let best_finish = best.and_then(|best| best.finish);
+    let x = (PADDING + LEFT_AREA_WIDTH) as i32 + 20;
+    let mut y = layout.board_top as i32 + 20;
+
+    draw_overlay(canvas, layout);
+    draw_text(canvas, font, "FINISHED", (x, y), Color::RGB(255, 215, 0));
+    draw_text(canvas, font, &format_time(finish), (x, y + 30), Color::WHITE);
+    if record{
+        draw_text(canvas, small_font, "NEW BEST", (x, y + 65), AHEAD_COLOR);
+    }else if let Some(best) = best_finish{
+        draw_text(canvas, small_font, &format!("BEST {}", format_time(best)), (x, y + 65), Color::WHITE);
+    }
+    if let Some(best) = best_finish{
+        let color = if finish < best {AHEAD_COLOR} else {BEHIND_COLOR};
+        draw_text(canvas, small_font, &format_delta(finish, best), (x + 110, y + 65), color);
+    }
+    y += 100;
+    let skipped = sprint.splits.len().saturating_sub(RESULT_SPLITS);
+    for (i, split) in sprint.splits.iter().enumerate().skip(skipped){
+        let best_split = best.and_then(|best| best.splits.get(i));
+        draw_split(canvas, small_font, i, *split, best_split.copied(), (x, y));
+        y += 20;
+    }
+    draw_footer(canvas, layout, small_font, seed, x);
+}
+
+/// time left, level and the score to beat in blitz and ultra
+pub fn draw_timed(canvas : &mut Canvas<Window>, game : &Game, best : Option<u32>, font : &Font<'_, 'static>, small_font : &Font<'_, 'static>){
+    let x = HOLD_MARGIN as i32;
+    let time_left = game.time_left().unwrap_or(0);
+    let color = if time_left <= HURRY_FRAMES {BEHIND_COLOR} else {Color::WHITE};
+    draw_text(canvas, font, "TIME", (x, 110), Color::WHITE);
+    draw_text(canvas, font, &format_time(time_left), (x, 135), color);
+    draw_text(canvas, small_font, &format!("LEVEL {}", game.get_level() + 1), (x, 165), Color::WHITE);
+    if let Some(best) = best{
+        draw_text(canvas, small_font, &format!("BEST {}", best), (x, 190), Color::WHITE);
+    }
+}
+
+/// the final score of a timed game over the board, against the best one before it,
+/// `record` is true when it was kept as the new best
+pub fn draw_timed_results(canvas : &mut Canvas<Window>, layout : &Layout, font : &Font<'_, 'static>, small_font : &Font<'_, 'static>, game : &Game, best : Option<u32>, record : bool){
+    let x = (PADDING + LEFT_AREA_WIDTH) as i32 + 20;
+    let y = layout.board_top as i32 + 20;
+
+    draw_overlay(canvas, layout);
+    draw_text(canvas, font, "TIME UP", (x, y), Color::RGB(255, 215, 0));
+    draw_text(canvas, font, &game.score.to_string(), (x, y + 30), Color::WHITE);
+    if record{
+        draw_text(canvas, small_font, "NEW BEST", (x, y + 65), AHEAD_COLOR);
+    }else if let Some(best) = best{
+        draw_text(canvas, small_font, &format!("BEST {}", best), (x, y + 65), Color::WHITE);
+    }
+    draw_stats(canvas, small_font, game, (x, y + 100));
+    draw_footer(canvas, layout, small_font, game.seed, x);
+}
+
+/// the final score of a marathon over the board, after the top out
+pub fn draw_game_over(canvas : &mut Canvas<Window>, layout : &Layout, font : &Font<'_, 'static>, small_font : &Font<'_, 'static>, game : &Game){
+    let x = (PADDING + LEFT_AREA_WIDTH) as i32 + 20;
+    let y = layout.board_top as i32 + 20;
+
+    draw_overlay(canvas, layout);
+    draw_text(canvas, font, "GAME OVER", (x, y), Color::RGB(255, 215, 0));
+    draw_text(canvas, font, &game.score.to_string(), (x, y + 30), Color::WHITE);
+    draw_stats(canvas, small_font, game, (x, y + 75));
+    draw_footer(canvas, layout, small_font, game.seed, x);
+}
+
+fn draw_stats(canvas : &mut Canvas<Window>, small_font : &Font<'_, 'static>, game : &Game, (x, y) : (i32, i32)){
+    let stats = [
+        format!("LINES {}", game.lines_cleared),
+        format!("LEVEL {}", game.get_level() + 1),
+        format!("SENT {}", game.lines_sent),
+    ];
+    for (i, stat) in stats.iter().enumerate(){
+        draw_text(canvas, small_font, stat, (x, y + i as i32 * 20), Color::WHITE);
+    }
+}
+
+// the seed plays the same pieces again with --seed
+fn draw_footer(canvas : &mut Canvas<Window>, layout : &Layout, small_font : &Font<'_, 'static>, seed : u64, x : i32){
+    let bottom = (layout.board_top + layout.board_height) as i32;
+    draw_text(canvas, small_font, &format!("SEED {}", seed), (x, bottom - 55), Color::WHITE);
+    draw_text(canvas, small_font, "R: AGAIN  ESC: QUIT", (x, bottom - 30), Color::WHITE);
+}
+
+// darkens the board so text on it can be read
+fn draw_overlay(canvas : &mut Canvas<Window>, layout : &Layout){
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 190));
+    let _ = canvas.fill_rect(Rect::new((PADDING + LEFT_AREA_WIDTH) as i32, layout.board_top as i32, layout.board_width, layout.board_height));
+    canvas.set_blend_mode(BlendMode::None);
+}
+
+/// shows a result screen until R starts a new game (false) or Escape quits (true)
+pub fn result_screen(events : &mut EventPump, mut draw : impl FnMut()) -> bool{
+    loop{
+        for event in events.poll_iter() {
+            match event {
+                Event::Quit {..} |
+                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return true,
+                Event::KeyDown { keycode: Some(Keycode::R), .. } => return false,
+                _ => {}
+            }
+        }
+        draw();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
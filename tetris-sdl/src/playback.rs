@@ -0,0 +1,68 @@
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::render::Canvas;
+use sdl2::TimerSubsystem;
+use sdl2::ttf::Font;
+use sdl2::video::Window;
+use sdl2::EventPump;
+use std::time::Duration;
+use tetris::mode::format_time;
+use tetris::replay::{Playback, Replay};
+
+use crate::{draw_game, draw_text, Layout, FPS, FRAME_MS, MAX_LAG_MS, PADDING, LEFT_AREA_WIDTH};
+
+// left and right arrows jump by 5 seconds
+const SEEK_FRAMES : u32 = 5 * FPS as u32;
+const MIN_SPEED : f64 = 0.125;
+const MAX_SPEED : f64 = 8.0;
+
+// Space pauses, left and right seek, up and down change the speed, R restarts, Escape quits
+pub fn play(canvas : &mut Canvas<Window>, events : &mut EventPump, timer : &TimerSubsystem, layout : &Layout, font : &Font<'_, 'static>, small_font : &Font<'_, 'static>, replay : Replay){
+    let mut playback = Playback::new(replay);
+    let mut paused = false;
+    let mut speed : f64 = 1.0;
+    let mut clock = timer.ticks() as f64;
+    // frames to simulate, fractional below 1x speed
+    let mut pending = 0.0;
+
+    loop{
+        for event in events.poll_iter() {
+            match event {
+                Event::Quit {..} |
+                Event::KeyDown { keycode: Some(Keycode::Escape), .. } => return,
+                Event::KeyDown { keycode: Some(Keycode::Space), .. } => paused = !paused,
+                Event::KeyDown { keycode: Some(Keycode::Left), .. } => playback.seek(playback.frame().saturating_sub(SEEK_FRAMES)),
+                Event::KeyDown { keycode: Some(Keycode::Right), .. } => playback.seek(playback.frame() + SEEK_FRAMES),
+                Event::KeyDown { keycode: Some(Keycode::Up), .. } => speed = (speed * 2.0).min(MAX_SPEED),
+                Event::KeyDown { keycode: Some(Keycode::Down), .. } => speed = (speed / 2.0).max(MIN_SPEED),
+                Event::KeyDown { keycode: Some(Keycode::R), .. } => playback.seek(0),
+                _ => {}
+            }
+        }
+
+        let now = timer.ticks() as f64;
+        let elapsed = (now - clock).min(MAX_LAG_MS);
+        clock = now;
+        if !paused && !playback.finished(){
+            pending += elapsed / FRAME_MS * speed;
+            while pending >= 1.0{
+                pending -= 1.0;
+                playback.step();
+            }
+        }
+
+        draw_game(canvas, layout, playback.game(), &playback.last_lock, font, small_font, playback.frame());
+        let status = format!(
+            "REPLAY {}x {} / {}{}",
+            speed,
+            format_time(playback.frame()),
+            format_time(playback.frames()),
+            if paused {" PAUSED"} else if playback.finished() {" END"} else {""},
+        );
+        draw_text(canvas, small_font, &status, ((PADDING + LEFT_AREA_WIDTH) as i32, 2), Color::WHITE);
+
+        canvas.present();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::tetris_engine::SpinType;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AttackTable {GUIDELINE, TETRIO}
+
+// extra lines sent by a combo in the guideline table, the last value repeats
+static GUIDELINE_COMBO : [u32; 12] = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5];
+
+const PERFECT_CLEAR_ATTACK : u32 = 10;
+
+impl AttackTable {
+    pub fn from_name(name : &str) -> Option<Self>{
+        match name {
+            "guideline" => Some(AttackTable::GUIDELINE),
+            "tetrio" => Some(AttackTable::TETRIO),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str{
+        match self {
+            AttackTable::GUIDELINE => "guideline",
+            AttackTable::TETRIO => "tetrio",
+        }
+    }
+
+    fn base(lines : u32, spin : SpinType) -> u32{
+        match (spin, lines) {
+            (SpinType::MINI, 2) => 1,
+            (SpinType::MINI, _) => 0,
+            (SpinType::FULL, l) => l * 2,
+            (SpinType::NONE, 4) => 4,
+            (SpinType::NONE, l) => l.saturating_sub(1),
+        }
+    }
+
+    /// lines sent by a lock, `combo` and `b2b` are the counters after the lock
+    pub fn attack(&self, lines : u32, spin : SpinType, combo : i32, b2b : i32, perfect_clear : bool) -> u32{
+        if lines == 0{
+            return 0;
+        }
+        let base = Self::base(lines, spin);
+        let difficult = lines == 4 || spin != SpinType::NONE;
+        let b2b = if difficult {b2b.max(0)} else {0};
+        let combo = combo.max(0);
+
+        let attack = match self {
+            AttackTable::GUIDELINE => {
+                let combo_bonus = GUIDELINE_COMBO[(combo as usize).min(GUIDELINE_COMBO.len() - 1)];
+                base + combo_bonus + if b2b > 0 {1} else {0}
+            },
+            AttackTable::TETRIO => {
+                let mut attack = base as f64;
+                if b2b > 0{
+                    let chain = (b2b as f64 * 0.8).ln_1p();
+                    attack += (1.0 + chain).floor();
+                    if b2b > 1{
+                        attack += (1.0 + chain % 1.0) / 3.0;
+                    }
+                }
+                attack *= 1.0 + 0.25 * combo as f64;
+                if combo > 1{
+                    attack = attack.max((combo as f64 * 1.25).ln_1p());
+                }
+                attack.floor() as u32
+            },
+        };
+
+        if perfect_clear {attack + PERFECT_CLEAR_ATTACK} else {attack}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guideline_attacks(){
+        let table = AttackTable::GUIDELINE;
+        let clear = |lines, spin| table.attack(lines, spin, 0, 0, false);
+        assert_eq!([1, 2, 3, 4].map(|lines| clear(lines, SpinType::NONE)), [0, 1, 2, 4]);
+        assert_eq!([1, 2, 3].map(|lines| clear(lines, SpinType::FULL)), [2, 4, 6]);
+        assert_eq!([1, 2].map(|lines| clear(lines, SpinType::MINI)), [0, 1]);
+        assert_eq!(clear(0, SpinType::FULL), 0);
+        // back to back only for tetrises and spins
+        assert_eq!(table.attack(4, SpinType::NONE, 0, 1, false), 5);
+        assert_eq!(table.attack(2, SpinType::FULL, 0, 3, false), 5);
+        assert_eq!(table.attack(2, SpinType::NONE, 0, 3, false), 1);
+        assert_eq!([1, 2, 4, 6, 11, 20].map(|combo| table.attack(1, SpinType::NONE, combo, -1, false)), [0, 1, 2, 3, 5, 5]);
+        assert_eq!(table.attack(1, SpinType::NONE, 0, -1, true), 10);
+    }
+
+    #[test]
+    fn tetrio_attacks(){
+        let table = AttackTable::TETRIO;
+        assert_eq!([1, 2, 3, 4].map(|lines| table.attack(lines, SpinType::NONE, 0, 0, false)), [0, 1, 2, 4]);
+        assert_eq!(table.attack(2, SpinType::FULL, 0, 0, false), 4);
+        // the back to back bonus grows with the chain
+        assert_eq!([1, 2, 4, 8].map(|b2b| table.attack(4, SpinType::NONE, 0, b2b, false)), [5, 5, 6, 7]);
+        // combos multiply the attack, long ones send even with singles
+        assert_eq!(table.attack(4, SpinType::NONE, 2, 0, false), 6);
+        assert_eq!([1, 4, 8].map(|combo| table.attack(1, SpinType::NONE, combo, -1, false)), [0, 1, 2]);
+        assert_eq!(table.attack(4, SpinType::NONE, 0, 0, true), 14);
+    }
+}
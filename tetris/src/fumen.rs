@@ -0,0 +1,739 @@
+use crate::tetris_engine::{piece_index, Game, GARBAGE, PIECES};
+
+/// fumen boards are always 10 wide, with 23 rows and a garbage row below them
+pub const FIELD_WIDTH : usize = 10;
+pub const FIELD_TOP : usize = 23;
+const FIELD_BLOCKS : usize = (FIELD_TOP + 1) * FIELD_WIDTH;
+
+const PREFIX : &str = "v115@";
+const BASE64 : &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+// characters a comment is made of once escaped, the value of each is its index
+const COMMENT_TABLE : &str = " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+const COMMENT_BASE : u32 = 96;
+const MAX_COMMENT : usize = 4095;
+// fumen piece values from 1 to 7, 8 is gray
+const FUMEN_PIECES : &str = "ILOZTJS";
+const FUMEN_GRAY : u32 = 8;
+// the unchanged field marker is followed by how many pages after it also keep the field
+const MAX_REPEAT : u32 = 63;
+
+/// a piece placed on a page, at its SRS rotation center with y going up from the bottom row
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Operation {
+    /// index in PIECES
+    pub piece : usize,
+    /// 0 spawn, 1 right, 2 reverse, 3 left
+    pub rotation : usize,
+    pub x : i32,
+    pub y : i32,
+}
+
+impl Operation {
+    /// the four cells of the piece, x and y like the operation
+    pub fn cells(&self) -> [(i32, i32); 4]{
+        let shape : [(i32, i32); 4] = match PIECES[self.piece].name {
+            'I' => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+            'T' => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+            'O' => [(0, 0), (1, 0), (0, 1), (1, 1)],
+            'L' => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+            'J' => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+            'S' => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+            _ => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+        };
+        shape.map(|(x, y)| {
+            let (x, y) = match self.rotation % 4 {
+                0 => (x, y),
+                1 => (y, -x),
+                2 => (-x, -y),
+                _ => (-y, x),
+            };
+            (self.x + x, self.y + y)
+        })
+    }
+}
+
+/// one page of a diagram, cells are board cell indices like `Game::board`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Page {
+    /// the rows, the bottom one first
+    pub field : [[u8; FIELD_WIDTH]; FIELD_TOP],
+    /// the row below the field that rises with the `rise` flag
+    pub garbage : [u8; FIELD_WIDTH],
+    pub operation : Option<Operation>,
+    pub comment : String,
+    /// the operation is locked and full lines are cleared before the next page
+    pub lock : bool,
+    pub rise : bool,
+    pub mirror : bool,
+    /// guideline colors, only read on the first page
+    pub colorize : bool,
+}
+
+impl Default for Page {
+    fn default() -> Self{
+        Page::new()
+    }
+}
+
+impl Page {
+    pub fn new() -> Self{
+        Self{
+            field : [[0; FIELD_WIDTH]; FIELD_TOP],
+            garbage : [0; FIELD_WIDTH],
+            operation : None,
+            comment : String::new(),
+            lock : true,
+            rise : false,
+            mirror : false,
+            colorize : true,
+        }
+    }
+
+    fn cell(&self, index : usize) -> u8{
+        let (x, row) = (index % FIELD_WIDTH, index / FIELD_WIDTH);
+        if row == FIELD_TOP {self.garbage[x]} else {self.field[FIELD_TOP - 1 - row][x]}
+    }
+
+    fn set_cell(&mut self, index : usize, cell : u8){
+        let (x, row) = (index % FIELD_WIDTH, index / FIELD_WIDTH);
+        if row == FIELD_TOP {self.garbage[x] = cell} else {self.field[FIELD_TOP - 1 - row][x] = cell}
+    }
+
+    /// the field the next page starts from
+    fn next_field(&self) -> Page{
+        let mut next = Page::new();
+        next.field = self.field;
+        next.garbage = self.garbage;
+        if !self.lock{
+            return next;
+        }
+        if let Some(operation) = self.operation{
+            for (x, y) in operation.cells(){
+                if x >= 0 && (x as usize) < FIELD_WIDTH && y >= 0 && (y as usize) < FIELD_TOP{
+                    next.field[y as usize][x as usize] = PIECES[operation.piece].index;
+                }
+            }
+        }
+        let mut rows : Vec<[u8; FIELD_WIDTH]> = next.field.iter().copied().filter(|row| row.contains(&0)).collect();
+        if self.rise{
+            rows.insert(0, next.garbage);
+            next.garbage = [0; FIELD_WIDTH];
+        }
+        // cleared rows come back empty at the top, a risen row pushes the top one out
+        rows.resize(FIELD_TOP, [0; FIELD_WIDTH]);
+        next.field.copy_from_slice(&rows);
+        if self.mirror{
+            for row in next.field.iter_mut(){
+                row.reverse();
+            }
+        }
+        next
+    }
+}
+
+fn to_fumen_cell(cell : u8) -> u32{
+    if cell == 0{
+        return 0;
+    }
+    PIECES.iter()
+        .find(|piece| piece.index == cell)
+        .and_then(|piece| FUMEN_PIECES.find(piece.name))
+        .map_or(FUMEN_GRAY, |value| value as u32 + 1)
+}
+
+fn from_fumen_cell(value : u32) -> u8{
+    match value {
+        0 => 0,
+        FUMEN_GRAY => GARBAGE,
+        _ => PIECES[fumen_piece(value).unwrap()].index,
+    }
+}
+
+// index in PIECES of a fumen piece value
+fn fumen_piece(value : u32) -> Option<usize>{
+    let name = FUMEN_PIECES.chars().nth((value as usize).checked_sub(1)?)?;
+    piece_index(name)
+}
+
+// fumen stores a rotation as reverse, right, spawn, left
+fn fumen_rotation(rotation : usize) -> u32{
+    [2, 1, 0, 3][rotation % 4]
+}
+
+// the encoded position is not the rotation center for some pieces
+fn position_shift(name : char, rotation : usize) -> (i32, i32){
+    match (name, rotation) {
+        ('O', 3) => (1, -1),
+        ('O', 2) => (1, 0),
+        ('O', 0) => (0, -1),
+        ('I', 2) => (1, 0),
+        ('I', 3) => (0, -1),
+        ('S', 0) => (0, -1),
+        ('S', 1) => (-1, 0),
+        ('Z', 0) => (0, -1),
+        ('Z', 3) => (1, 0),
+        _ => (0, 0),
+    }
+}
+
+struct Values {
+    values : Vec<u32>,
+    position : usize,
+}
+
+impl Values {
+    fn poll(&mut self, digits : usize) -> Result<u32, String>{
+        let mut value = 0;
+        for i in 0..digits{
+            let digit = self.values.get(self.position + i).ok_or("the fumen is cut short")?;
+            value += digit * 64u32.pow(i as u32);
+        }
+        self.position += digits;
+        Ok(value)
+    }
+
+    fn push(&mut self, value : u32, digits : usize){
+        let mut value = value;
+        for _ in 0..digits{
+            self.values.push(value % 64);
+            value /= 64;
+        }
+    }
+
+    fn is_empty(&self) -> bool{
+        self.position >= self.values.len()
+    }
+}
+
+/// reads a v115 fumen, the `v115@` prefix can be in the middle of a URL
+pub fn decode(fumen : &str) -> Result<Vec<Page>, String>{
+    let start = ["v115@", "m115@", "d115@"].iter()
+        .filter_map(|prefix| fumen.find(prefix))
+        .min()
+        .ok_or("only v115 fumens are supported")?;
+    let mut values = Values{ values : Vec::new(), position : 0 };
+    for c in fumen[start + PREFIX.len()..].chars().filter(|c| *c != '?' && !c.is_whitespace()){
+        let digit = BASE64.iter().position(|d| *d as char == c).ok_or(format!("bad fumen character: {}", c))?;
+        values.values.push(digit as u32);
+    }
+
+    let mut pages : Vec<Page> = Vec::new();
+    let mut previous = Page::new();
+    let mut repeat = 0;
+    while !values.is_empty(){
+        let mut page = previous.clone();
+        if repeat > 0{
+            repeat -= 1;
+        }else{
+            let mut index = 0;
+            while index < FIELD_BLOCKS{
+                let run = values.poll(2)?;
+                let (diff, count) = (run / FIELD_BLOCKS as u32, run as usize % FIELD_BLOCKS + 1);
+                if diff == 8 && count == FIELD_BLOCKS{
+                    repeat = values.poll(1)?;
+                }
+                if index + count > FIELD_BLOCKS{
+                    return Err("bad fumen field".to_string());
+                }
+                for i in index..index + count{
+                    let value = to_fumen_cell(page.cell(i)) as i32 + diff as i32 - 8;
+                    if !(0..=FUMEN_GRAY as i32).contains(&value){
+                        return Err("bad fumen field".to_string());
+                    }
+                    page.set_cell(i, from_fumen_cell(value as u32));
+                }
+                index += count;
+            }
+        }
+
+        let mut action = values.poll(3)?;
+        let piece = action % 8;
+        action /= 8;
+        let rotation = [2, 1, 0, 3][(action % 4) as usize];
+        action /= 4;
+        let position = action as usize % FIELD_BLOCKS;
+        action /= FIELD_BLOCKS as u32;
+        page.rise = action % 2 == 1;
+        page.mirror = (action / 2) % 2 == 1;
+        page.colorize = (action / 4) % 2 == 1;
+        let has_comment = (action / 8) % 2 == 1;
+        page.lock = (action / 16) % 2 == 0;
+
+        page.operation = match fumen_piece(piece) {
+            Some(piece) => {
+                let (shift_x, shift_y) = position_shift(PIECES[piece].name, rotation);
+                Some(Operation{
+                    piece,
+                    rotation,
+                    x : (position % FIELD_WIDTH) as i32 + shift_x,
+                    y : (FIELD_TOP - 1 - position / FIELD_WIDTH) as i32 + shift_y,
+                })
+            },
+            None => None,
+        };
+        // without a comment of its own a page keeps the one before
+        page.comment = match (has_comment, pages.last()) {
+            (true, _) => decode_comment(&mut values)?,
+            (false, Some(last)) => last.comment.clone(),
+            (false, None) => String::new(),
+        };
+
+        previous = page.next_field();
+        pages.push(page);
+    }
+    if pages.is_empty(){
+        return Err("the fumen is empty".to_string());
+    }
+    Ok(pages)
+}
+
+fn decode_comment(values : &mut Values) -> Result<String, String>{
+    let length = values.poll(2)? as usize;
+    let table : Vec<char> = COMMENT_TABLE.chars().collect();
+    let mut escaped = String::new();
+    while escaped.len() < length{
+        let mut value = values.poll(5)?;
+        for _ in 0..4{
+            if escaped.len() < length{
+                escaped.push(*table.get((value % COMMENT_BASE) as usize).ok_or("bad fumen comment")?);
+            }
+            value /= COMMENT_BASE;
+        }
+    }
+    Ok(unescape(&escaped))
+}
+
+/// writes the pages as a v115 fumen
+pub fn encode(pages : &[Page]) -> String{
+    let mut values = Values{ values : Vec::new(), position : 0 };
+    let mut previous = Page::new();
+    let mut previous_comment = "";
+    // where the count of pages repeating the field is, while the field doesn't change
+    let mut repeat_index : Option<usize> = None;
+    for (i, page) in pages.iter().enumerate(){
+        let diffs : Vec<u32> = (0..FIELD_BLOCKS)
+            .map(|index| to_fumen_cell(page.cell(index)) + 8 - to_fumen_cell(previous.cell(index)))
+            .collect();
+        let unchanged = diffs.iter().all(|diff| *diff == 8);
+        match repeat_index {
+            Some(index) if unchanged && values.values[index] < MAX_REPEAT => values.values[index] += 1,
+            _ => {
+                let mut start = 0;
+                while start < FIELD_BLOCKS{
+                    let count = diffs[start..].iter().take_while(|diff| **diff == diffs[start]).count();
+                    values.push(diffs[start] * FIELD_BLOCKS as u32 + count as u32 - 1, 2);
+                    start += count;
+                }
+                repeat_index = None;
+                if unchanged{
+                    values.push(0, 1);
+                    repeat_index = Some(values.values.len() - 1);
+                }
+            },
+        }
+
+        let has_comment = page.comment != previous_comment;
+        let (piece, rotation, position) = match page.operation {
+            Some(operation) => {
+                let (shift_x, shift_y) = position_shift(PIECES[operation.piece].name, operation.rotation % 4);
+                let (x, y) = (operation.x - shift_x, operation.y - shift_y);
+                let position = (FIELD_TOP as i32 - 1 - y) * FIELD_WIDTH as i32 + x;
+                (
+                    FUMEN_PIECES.find(PIECES[operation.piece].name).unwrap() as u32 + 1,
+                    fumen_rotation(operation.rotation),
+                    position.clamp(0, FIELD_BLOCKS as i32 - 1) as u32,
+                )
+            },
+            None => (0, 0, 0),
+        };
+        let mut action = !page.lock as u32;
+        action = action * 2 + has_comment as u32;
+        action = action * 2 + (page.colorize && i == 0) as u32;
+        action = action * 2 + page.mirror as u32;
+        action = action * 2 + page.rise as u32;
+        action = action * FIELD_BLOCKS as u32 + position;
+        action = action * 4 + rotation;
+        action = action * 8 + piece;
+        values.push(action, 3);
+        if has_comment{
+            encode_comment(&mut values, &page.comment);
+            previous_comment = &page.comment;
+        }
+
+        previous = page.next_field();
+    }
+
+    let data : String = values.values.iter().map(|value| BASE64[*value as usize] as char).collect();
+    // fumen breaks long data with `?`, after 42 characters then every 47
+    let mut fumen = PREFIX.to_string();
+    for (i, c) in data.chars().enumerate(){
+        if data.len() >= 41 && i >= 42 && (i - 42) % 47 == 0{
+            fumen.push('?');
+        }
+        fumen.push(c);
+    }
+    fumen
+}
+
+fn encode_comment(values : &mut Values, comment : &str){
+    // long comments are cut between characters, never in the middle of an escape sequence
+    let mut escaped = String::new();
+    for c in comment.chars(){
+        let part = escape(c.encode_utf8(&mut [0; 4]));
+        if escaped.len() + part.len() > MAX_COMMENT{
+            break;
+        }
+        escaped.push_str(&part);
+    }
+    let escaped : Vec<u32> = escaped.chars()
+        .map(|c| COMMENT_TABLE.find(c).unwrap() as u32)
+        .collect();
+    values.push(escaped.len() as u32, 2);
+    for chunk in escaped.chunks(4){
+        let value = chunk.iter().rev().fold(0, |value, c| value * COMMENT_BASE + c);
+        values.push(value, 5);
+    }
+}
+
+// javascript escape(), fumen comments are stored that way
+fn escape(text : &str) -> String{
+    let mut escaped = String::new();
+    for unit in text.encode_utf16(){
+        match char::from_u32(unit as u32) {
+            Some(c) if c.is_ascii_alphanumeric() || "@*_+-./".contains(c) => escaped.push(c),
+            _ if unit < 256 => escaped.push_str(&format!("%{:02X}", unit)),
+            _ => escaped.push_str(&format!("%u{:04X}", unit)),
+        }
+    }
+    escaped
+}
+
+fn unescape(text : &str) -> String{
+    let mut units : Vec<u16> = Vec::new();
+    let chars : Vec<char> = text.chars().collect();
+    let mut i = 0;
+    while i < chars.len(){
+        let hex = |from : usize, digits : usize| -> Option<u16>{
+            let digits : String = chars.get(from..from + digits)?.iter().collect();
+            u16::from_str_radix(&digits, 16).ok()
+        };
+        if chars[i] == '%'{
+            if chars.get(i + 1) == Some(&'u'){
+                if let Some(unit) = hex(i + 2, 4){
+                    units.push(unit);
+                    i += 6;
+                    continue;
+                }
+            }else if let Some(unit) = hex(i + 1, 2){
+                units.push(unit);
+                i += 3;
+                continue;
+            }
+        }
+        units.push(chars[i] as u16);
+        i += 1;
+    }
+    String::from_utf16_lossy(&units)
+}
+
+/// the game as a one page fumen: the board, the piece in play and a `#Q=[hold](current)next` queue comment
+pub fn from_game(game : &Game) -> Result<String, String>{
+    if game.width() != FIELD_WIDTH{
+        return Err("fumen boards are 10 wide".to_string());
+    }
+    let mut page = Page::new();
+    for (y, row) in page.field.iter_mut().enumerate(){
+        if let Some(board_row) = game.board.len().checked_sub(y + 1){
+            row.copy_from_slice(&game.board[board_row]);
+        }
+    }
+    if game.piece_active(){
+        page.operation = game_operation(game);
+        // the piece in play is shown but not locked in
+        page.lock = false;
+    }
+    let hold = game.hold_piece.map_or(String::new(), |piece| piece.name.to_string());
+    let (current, next) = game_queue(game);
+    let next : String = next.iter().take(game.get_nexts().len()).map(|piece| PIECES[*piece].name).collect();
+    page.comment = format!("#Q=[{}]({}){}", hold, PIECES[current].name, next);
+    Ok(encode(&[page]))
+}
+
+// the piece in play and the queue after it, between pieces the first next piece is the one to come
+fn game_queue(game : &Game) -> (usize, Vec<usize>){
+    let mut next : Vec<usize> = game.next_pieces.iter().copied().collect();
+    if game.piece_active(){
+        return (piece_index(game.current_piece.name).unwrap(), next);
+    }
+    let current = next.remove(0);
+    (current, next)
+}
+
+// the piece in play where fumen puts it, if its shape is one fumen knows
+fn game_operation(game : &Game) -> Option<Operation>{
+    let tetromino = game.current_tetromino();
+    let (pos_x, pos_y) = game.current_position;
+    let mut cells : Vec<(i32, i32)> = tetromino.iter()
+        .map(|(x, y)| ((pos_x + x) as i32, (game.board.len() as i32 - 1) - (pos_y - y) as i32))
+        .collect();
+    cells.sort();
+    let piece = piece_index(game.current_piece.name)?;
+    // other rotation systems can have a different shape for the same rotation
+    let rotations = [game.current_rotation, 0, 1, 2, 3];
+    for rotation in rotations{
+        let shape = Operation{ piece, rotation, x : 0, y : 0 };
+        let mut shape_cells = shape.cells();
+        shape_cells.sort();
+        let (x, y) = (cells[0].0 - shape_cells[0].0, cells[0].1 - shape_cells[0].1);
+        let operation = Operation{ piece, rotation, x, y };
+        let mut operation_cells = operation.cells();
+        operation_cells.sort();
+        if operation_cells[..] == cells[..]{
+            return Some(operation);
+        }
+    }
+    None
+}
+
+/// starts the game from the first page: its field, then the queue of a `#Q=` comment, or
+/// its operation as the piece in play followed by the pieces of the next pages
+pub fn load_into(game : &mut Game, pages : &[Page]) -> Result<(), String>{
+    if game.width() != FIELD_WIDTH{
+        return Err("fumen boards are 10 wide".to_string());
+    }
+    let page = pages.first().ok_or("the fumen is empty")?;
+    let height = game.board.len();
+    for row in game.board.iter_mut(){
+        row.fill(0);
+    }
+    for (y, row) in page.field.iter().enumerate().take(height){
+        game.board[height - 1 - y].copy_from_slice(row);
+    }
+
+    if let Some((hold, current, next)) = parse_quiz(&page.comment){
+        game.set_pieces(current, hold, &next);
+        if let Some(operation) = page.operation.filter(|operation| operation.piece == current){
+            place_operation(game, operation);
+        }
+        return Ok(());
+    }
+    let mut queue : Vec<usize> = pages.iter()
+        .filter_map(|page| page.operation.map(|operation| operation.piece))
+        .collect();
+    if queue.is_empty(){
+        // no pieces in the fumen, the game keeps its own on the new board
+        let hold = game.hold_piece.and_then(|piece| piece_index(piece.name));
+        let (current, queue) = game_queue(game);
+        game.set_pieces(current, hold, &queue);
+        return Ok(());
+    }
+    let current = queue.remove(0);
+    game.set_pieces(current, None, &queue);
+    if let Some(operation) = page.operation{
+        place_operation(game, operation);
+    }
+    Ok(())
+}
+
+// #Q=[hold](current)next
+fn parse_quiz(comment : &str) -> Option<(Option<usize>, usize, Vec<usize>)>{
+    let quiz = comment.strip_prefix("#Q=[")?;
+    let (hold, rest) = quiz.split_once("](")?;
+    let (current, next) = rest.split_once(')')?;
+    let piece = |name : &str| name.chars().next().and_then(piece_index).filter(|_| name.len() == 1);
+    let hold = if hold.is_empty() {None} else {Some(piece(hold)?)};
+    let next = next.chars().take_while(|c| !c.is_whitespace()).map(piece_index).collect::<Option<Vec<usize>>>()?;
+    Some((hold, piece(current)?, next))
+}
+
+// moves the piece in play where the operation has it, if the board lets it
+fn place_operation(game : &mut Game, operation : Operation){
+    let mut cells : Vec<(i32, i32)> = operation.cells().iter()
+        .map(|(x, y)| (*x, game.board.len() as i32 - 1 - y))
+        .collect();
+    cells.sort();
+    for rotation in [operation.rotation, 0, 1, 2, 3]{
+        let tetromino = game.rotation_system.tetromino(game.current_piece, rotation);
+        // board cells are (pos.x + x, pos.y - y)
+        let mut offsets : Vec<(i32, i32)> = tetromino.iter().map(|(x, y)| (*x as i32, -*y as i32)).collect();
+        offsets.sort();
+        let position = (cells[0].0 - offsets[0].0, cells[0].1 - offsets[0].1);
+        let mut placed : Vec<(i32, i32)> = offsets.iter().map(|(x, y)| (position.0 + x, position.1 + y)).collect();
+        placed.sort();
+        if placed != cells{
+            continue;
+        }
+        let position = (position.0 as i8, position.1 as i8);
+        if game.check_tetromino(position, &tetromino){
+            game.current_rotation = rotation;
+            game.current_position = position;
+        }
+        return;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tetris_engine::Rules;
+    use crate::user_controls::{Action, Handling, UserControl};
+
+    fn piece(name : char) -> usize{
+        piece_index(name).unwrap()
+    }
+
+    fn pages() -> Vec<Page>{
+        let mut first = Page::new();
+        first.field[0] = [GARBAGE, GARBAGE, GARBAGE, 0, GARBAGE, GARBAGE, GARBAGE, GARBAGE, GARBAGE, GARBAGE];
+        first.field[1][4..8].copy_from_slice(&[PIECES[piece('L')].index; 4]);
+        first.operation = Some(Operation{ piece : piece('I'), rotation : 1, x : 3, y : 1 });
+        first.comment = "opener".to_string();
+
+        let mut rise = first.next_field();
+        rise.garbage = [GARBAGE, GARBAGE, GARBAGE, GARBAGE, 0, GARBAGE, GARBAGE, GARBAGE, GARBAGE, GARBAGE];
+        rise.operation = Some(Operation{ piece : piece('T'), rotation : 2, x : 7, y : 1 });
+        rise.rise = true;
+        rise.colorize = false;
+        rise.comment = first.comment.clone();
+
+        let mut mirror = rise.next_field();
+        mirror.operation = Some(Operation{ piece : piece('O'), rotation : 0, x : 0, y : 2 });
+        mirror.mirror = true;
+        mirror.colorize = false;
+        mirror.comment = "100% テトリス!".to_string();
+
+        let mut unlocked = mirror.next_field();
+        unlocked.operation = Some(Operation{ piece : piece('S'), rotation : 3, x : 5, y : 4 });
+        unlocked.lock = false;
+        unlocked.colorize = false;
+        unlocked.comment = String::new();
+        vec![first, rise, mirror, unlocked]
+    }
+
+    #[test]
+    fn pages_round_trip(){
+        let pages = pages();
+        let fumen = encode(&pages);
+        assert_eq!(decode(&fumen), Ok(pages));
+        assert_eq!(encode(&decode(&fumen).unwrap()), fumen);
+    }
+
+    #[test]
+    fn flags_change_the_next_field(){
+        let pages = decode(&encode(&pages())).unwrap();
+        // the I fills the hole and clears the bottom row
+        assert!(pages[1].field[0].contains(&0));
+        assert_eq!(pages[1].field[0][3..8], [1, 7, 7, 7, 7]);
+        // the garbage row rose under the T, then the mirror flipped everything
+        assert_eq!(pages[2].field[0], [GARBAGE, GARBAGE, GARBAGE, GARBAGE, 0, GARBAGE, GARBAGE, GARBAGE, GARBAGE, GARBAGE]);
+        assert_eq!(pages[2].garbage, [0; FIELD_WIDTH]);
+        assert_eq!(pages[3].field[0], [GARBAGE, GARBAGE, GARBAGE, GARBAGE, GARBAGE, 0, GARBAGE, GARBAGE, GARBAGE, GARBAGE]);
+        // the S is not locked, the page after it starts without it
+        assert_eq!(pages[3].next_field().field, pages[3].field);
+    }
+
+    #[test]
+    fn unchanged_fields_repeat(){
+        let mut pages = vec![Page::new(); 70];
+        for (i, page) in pages.iter_mut().enumerate().skip(1){
+            page.colorize = false;
+            page.comment = format!("page {}", i);
+        }
+        let fumen = encode(&pages);
+        assert_eq!(decode(&fumen), Ok(pages));
+        // a page without a comment of its own keeps the one before
+        let pages = decode(&encode(&[Page::new(), Page{ comment : "kept".to_string(), ..Page::new() }, Page{ comment : "kept".to_string(), colorize : false, ..Page::new() }])).unwrap();
+        assert_eq!(pages[2].comment, "kept");
+    }
+
+    #[test]
+    fn long_comments_are_cut_between_characters(){
+        for (text, kept) in [("a", MAX_COMMENT), ("テ", MAX_COMMENT / 6), ("%", MAX_COMMENT / 3), ("🧱", MAX_COMMENT / 12)]{
+            let page = Page{ comment : text.repeat(MAX_COMMENT), ..Page::new() };
+            let pages = decode(&encode(&[page])).unwrap();
+            assert_eq!(pages[0].comment, text.repeat(kept));
+        }
+    }
+
+    #[test]
+    fn known_fumen(){
+        let pages = decode("https://fumen.zui.jp/?v115@vhAVPJ").unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].field, [[0; FIELD_WIDTH]; FIELD_TOP]);
+        assert!(pages[0].operation.is_some());
+        assert_eq!(encode(&pages), "v115@vhAVPJ");
+        assert!(decode("v115@vhAVP").is_err());
+        assert!(decode("v115@!!").is_err());
+        assert!(decode("v114@vhAVPJ").is_err());
+    }
+
+    // O, I, S and Z in all four rotations, encoded by hand with the position shifts of the
+    // tetris-fumen reference encoder rather than with `encode`: O at (0,0) up, I (2,1) left,
+    // S (4,1) right, Z (7,0) up, O (9,3) down, I (5,4) down, Z (1,3) left and S (7,5) up,
+    // with its rotation centers, each one locked on the page after it
+    #[test]
+    fn fumen_piece_positions(){
+        let fumen = "v115@vhHTJJZlBvrB0sBDjBBcBcfB3TB";
+        let pages = decode(fumen).unwrap();
+        assert_eq!(pages.len(), 8);
+        let rows = ["2210040550", "2210445500", "5010400022", "5510000022", "0501111000", "0000004400", "0000000440"];
+        let mut expected = [[0; FIELD_WIDTH]; FIELD_TOP];
+        for (y, row) in rows.iter().enumerate(){
+            for (x, cell) in row.chars().enumerate(){
+                expected[y][x] = cell.to_digit(10).unwrap() as u8;
+            }
+        }
+        assert_eq!(pages[7].next_field().field, expected);
+        assert_eq!(encode(&pages), fumen);
+    }
+
+    #[test]
+    fn between_pieces_the_next_piece_is_current(){
+        let mut rules = Rules::new();
+        rules.are = 10;
+        let mut game = Game::new(Rules::new(), 4);
+        let mut waiting = Game::new(rules.clone(), 4);
+        game.hard_drop();
+        waiting.hard_drop();
+        assert!(game.piece_active() && !waiting.piece_active());
+        // the last next piece isn't drawn yet while waiting
+        let quiz = |game : &Game| decode(&from_game(game).unwrap()).unwrap()[0].comment.clone();
+        assert!(quiz(&game).starts_with(&quiz(&waiting)));
+        assert_eq!(quiz(&waiting).len(), quiz(&game).len() - 1);
+
+        load_into(&mut waiting, &decode("v115@vhAAgH").unwrap()).unwrap();
+        assert_eq!(waiting.current_piece.name, game.current_piece.name);
+        let names = |game : &Game| game.get_nexts().iter().map(|piece| piece.name).take(4).collect::<String>();
+        assert_eq!(names(&waiting), names(&game));
+    }
+
+    #[test]
+    fn game_round_trip(){
+        let mut game = Game::new(Rules::new(), 4);
+        let mut controls = UserControl::new(Handling::new());
+        for action in [Action::DROP, Action::HOLD, Action::LEFT, Action::DROP, Action::ROTATE_C, Action::DROP]{
+            controls.action(&mut game, action, true);
+            controls.action(&mut game, action, false);
+            controls.update(&mut game);
+        }
+        game.soft_drop();
+        assert!(game.score > 0);
+
+        let mut loaded = Game::new(Rules::new(), 5);
+        for _ in 0..3{
+            loaded.hard_drop();
+        }
+        loaded.receive_garbage(3, None);
+        load_into(&mut loaded, &decode(&from_game(&game).unwrap()).unwrap()).unwrap();
+        assert_eq!(loaded.board, game.board);
+        assert_eq!(loaded.current_piece.name, game.current_piece.name);
+        assert_eq!(loaded.current_position, game.current_position);
+        assert_eq!(loaded.current_rotation, game.current_rotation);
+        assert_eq!(loaded.hold_piece.map(|piece| piece.name), game.hold_piece.map(|piece| piece.name));
+        let names = |game : &Game| game.get_nexts().iter().map(|piece| piece.name).collect::<String>();
+        assert_eq!(names(&loaded), names(&game));
+        // a new position starts a new game
+        assert_eq!((loaded.score, loaded.lines_cleared, loaded.frames()), (0, 0, 0));
+        assert_eq!(loaded.garbage.batches().count(), 0);
+    }
+}
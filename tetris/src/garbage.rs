@@ -0,0 +1,113 @@
+use std::collections::VecDeque;
+
+/// a group of garbage lines received together, they all share the same hole
+/// unless messiness moves it
+#[derive(Copy, Clone, Debug)]
+pub struct GarbageBatch {
+    pub lines : u32,
+    pub hole : usize,
+}
+
+#[derive(Clone)]
+pub struct GarbageQueue {
+    batches : VecDeque<GarbageBatch>,
+}
+
+impl Default for GarbageQueue {
+    fn default() -> Self{
+        GarbageQueue::new()
+    }
+}
+
+impl GarbageQueue {
+    pub fn new() -> Self{
+        Self{
+            batches : VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, lines : u32, hole : usize){
+        if lines > 0{
+            self.batches.push_back(GarbageBatch { lines, hole });
+        }
+    }
+
+    pub fn batches(&self) -> impl Iterator<Item = &GarbageBatch>{
+        self.batches.iter()
+    }
+
+    /// removes up to `lines` from the oldest batches, returns what was left over
+    pub fn cancel(&mut self, mut lines : u32) -> u32{
+        while lines > 0{
+            let batch = match self.batches.front_mut() {
+                Some(batch) => batch,
+                None => break,
+            };
+            if batch.lines > lines{
+                batch.lines -= lines;
+                return 0;
+            }
+            lines -= batch.lines;
+            self.batches.pop_front();
+        }
+        lines
+    }
+
+    /// takes at most `cap` lines out of the queue, splitting a batch if needed
+    pub fn take(&mut self, cap : u32) -> Vec<GarbageBatch>{
+        let mut taken = Vec::new();
+        let mut left = cap;
+        while left > 0{
+            let batch = match self.batches.front_mut() {
+                Some(batch) => batch,
+                None => break,
+            };
+            if batch.lines > left{
+                batch.lines -= left;
+                taken.push(GarbageBatch { lines : left, hole : batch.hole });
+                break;
+            }
+            left -= batch.lines;
+            taken.push(*batch);
+            self.batches.pop_front();
+        }
+        taken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(queue : &GarbageQueue) -> Vec<(u32, usize)>{
+        queue.batches().map(|batch| (batch.lines, batch.hole)).collect()
+    }
+
+    #[test]
+    fn cancel_takes_from_the_oldest_batch(){
+        let mut queue = GarbageQueue::new();
+        queue.push(3, 1);
+        queue.push(0, 2);
+        queue.push(4, 5);
+        assert_eq!(lines(&queue), [(3, 1), (4, 5)]);
+        assert_eq!(queue.cancel(2), 0);
+        assert_eq!(lines(&queue), [(1, 1), (4, 5)]);
+        assert_eq!(queue.cancel(3), 0);
+        assert_eq!(lines(&queue), [(2, 5)]);
+        // what is not cancelled is sent
+        assert_eq!(queue.cancel(5), 3);
+        assert_eq!(lines(&queue), []);
+    }
+
+    #[test]
+    fn take_splits_a_batch_at_the_cap(){
+        let mut queue = GarbageQueue::new();
+        queue.push(3, 1);
+        queue.push(4, 5);
+        let taken : Vec<(u32, usize)> = queue.take(5).iter().map(|batch| (batch.lines, batch.hole)).collect();
+        assert_eq!(taken, [(3, 1), (2, 5)]);
+        assert_eq!(lines(&queue), [(2, 5)]);
+        assert_eq!(queue.take(8).len(), 1);
+        assert!(queue.take(8).is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+/// rows per frame at which pieces land as soon as they spawn
+pub const TWENTY_G : f32 = 20.0;
+
+// frames per row in NES for levels 0 to 28, level 29 and up is 1
+static NES_FRAMES : [u32; 29] = [48, 43, 38, 33, 28, 23, 18, 13, 8, 6, 5, 5, 5, 4, 4, 4, 3, 3, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2];
+
+// TGM internal gravity, (level, 256ths of a row per frame) from that level on
+static TGM_GRAVITY : [(u32, u32); 30] = [
+    (0, 4), (30, 6), (35, 8), (40, 10), (50, 12), (60, 16), (70, 32), (80, 48), (90, 64), (100, 80),
+    (120, 96), (140, 112), (160, 128), (170, 144), (200, 4), (220, 32), (230, 64), (233, 96), (236, 128), (239, 160),
+    (243, 192), (247, 224), (251, 256), (300, 512), (330, 768), (360, 1024), (400, 1280), (420, 1024), (450, 768), (500, 5120),
+];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GravityCurve {
+    // (0.8 - (level - 1) * 0.007) ^ (level - 1) seconds per row
+    GUIDELINE,
+    NES,
+    // the TGM level goes up by one for every line cleared
+    TGM,
+    // the same rows per frame at every level
+    FIXED(f32),
+}
+
+impl GravityCurve {
+    pub fn from_name(name : &str) -> Option<Self>{
+        match name {
+            "guideline" => Some(GravityCurve::GUIDELINE),
+            "nes" => Some(GravityCurve::NES),
+            "tgm" => Some(GravityCurve::TGM),
+            "20g" => Some(GravityCurve::FIXED(TWENTY_G)),
+            _ => name.parse().ok().filter(|rows : &f32| *rows > 0.0).map(GravityCurve::FIXED),
+        }
+    }
+
+    pub fn name(&self) -> String{
+        match self {
+            GravityCurve::GUIDELINE => "guideline".to_string(),
+            GravityCurve::NES => "nes".to_string(),
+            GravityCurve::TGM => "tgm".to_string(),
+            GravityCurve::FIXED(rows) => rows.to_string(),
+        }
+    }
+
+    /// rows per frame at a level that goes up at its own pace, TGM still follows `lines`
+    pub fn gravity_at_level(&self, level : u32, lines : u32) -> f32{
+        let gravity = match self {
+            GravityCurve::GUIDELINE => {
+                let level = level as f32;
+                let seconds = (0.8 - level * 0.007).max(0.0).powf(level);
+                if seconds > 0.0 {1.0 / (seconds * 60.0)} else {TWENTY_G}
+            },
+            GravityCurve::NES => 1.0 / *NES_FRAMES.get(level as usize).unwrap_or(&1) as f32,
+            GravityCurve::TGM => {
+                let (_, internal) = TGM_GRAVITY.iter()
+                    .rev()
+                    .find(|(from, _)| lines >= *from)
+                    .unwrap();
+                *internal as f32 / 256.0
+            },
+            GravityCurve::FIXED(rows) => *rows,
+        };
+        gravity.min(TWENTY_G)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guideline_speeds(){
+        // level 0 is the guideline level 1: a row a second, about 2.36 rows a frame at level 15
+        assert_eq!(GravityCurve::GUIDELINE.gravity_at_level(0, 0), 1.0 / 60.0);
+        assert!((GravityCurve::GUIDELINE.gravity_at_level(14, 0) - 2.36).abs() < 0.01);
+        assert_eq!(GravityCurve::GUIDELINE.gravity_at_level(19, 0), TWENTY_G);
+    }
+
+    #[test]
+    fn nes_frames_per_row(){
+        for (level, frames) in [(0, 48), (8, 8), (9, 6), (18, 3), (19, 2), (28, 2), (29, 1), (40, 1)]{
+            assert_eq!(GravityCurve::NES.gravity_at_level(level, 0), 1.0 / frames as f32);
+        }
+    }
+
+    #[test]
+    fn tgm_internal_gravity(){
+        // the level doesn't matter, only the lines
+        for (lines, internal) in [(0, 4), (29, 4), (30, 6), (199, 144), (200, 4), (251, 256), (499, 768)]{
+            assert_eq!(GravityCurve::TGM.gravity_at_level(0, lines), internal as f32 / 256.0);
+        }
+        assert_eq!(GravityCurve::TGM.gravity_at_level(0, 500), TWENTY_G);
+        assert_eq!(GravityCurve::from_name("20g"), Some(GravityCurve::FIXED(TWENTY_G)));
+    }
+}
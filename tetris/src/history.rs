@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+
+use crate::tetris_engine::Game;
+
+/// undo and redo of piece placements for practice, the game is saved after every lock
+pub struct History {
+    // games right after each of the last locks, the oldest first
+    undo : VecDeque<Game>,
+    redo : Vec<Game>,
+    // the game after the last lock, or at the start
+    current : Option<Game>,
+    limit : usize,
+}
+
+impl History {
+    /// keeps the last `limit` placements, 0 turns undo off
+    pub fn new(limit : usize) -> Self{
+        Self{
+            undo : VecDeque::new(),
+            redo : Vec::new(),
+            current : None,
+            limit,
+        }
+    }
+
+    pub fn start(&mut self, game : &Game){
+        self.undo.clear();
+        self.redo.clear();
+        self.current = if self.limit > 0 {Some(game.clone())} else {None};
+    }
+
+    /// to call after every lock, a new placement drops everything that was undone
+    pub fn locked(&mut self, game : &Game){
+        if self.limit == 0{
+            return;
+        }
+        if let Some(previous) = self.current.replace(game.clone()){
+            self.undo.push_back(previous);
+            if self.undo.len() > self.limit{
+                self.undo.pop_front();
+            }
+        }
+        self.redo.clear();
+    }
+
+    /// goes back to right after the lock before the last one, the piece in play is lost;
+    /// the clock keeps running so timed modes can't be undone into more time
+    pub fn undo(&mut self, game : &mut Game) -> bool{
+        let Some(previous) = self.undo.pop_back() else {
+            return false;
+        };
+        if let Some(current) = self.current.replace(previous.clone()){
+            self.redo.push(current);
+        }
+        let frames = game.frames;
+        *game = previous;
+        game.frames = frames;
+        true
+    }
+
+    pub fn redo(&mut self, game : &mut Game) -> bool{
+        let Some(next) = self.redo.pop() else {
+            return false;
+        };
+        if let Some(current) = self.current.replace(next.clone()){
+            self.undo.push_back(current);
+        }
+        let frames = game.frames;
+        *game = next;
+        game.frames = frames;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tetris_engine::Rules;
+
+    // a history of `placements` hard drops, with the board after each one
+    fn played(limit : usize, placements : usize) -> (Game, History, Vec<Vec<Vec<u8>>>){
+        let mut game = Game::new(Rules::new(), 3);
+        let mut history = History::new(limit);
+        history.start(&game);
+        let mut boards = vec![game.board.clone()];
+        for _ in 0..placements{
+            game.hard_drop();
+            history.locked(&game);
+            boards.push(game.board.clone());
+        }
+        (game, history, boards)
+    }
+
+    #[test]
+    fn undo_and_redo(){
+        let (mut game, mut history, boards) = played(5, 3);
+        assert!(history.undo(&mut game));
+        assert_eq!(game.board, boards[2]);
+        assert!(history.undo(&mut game));
+        assert_eq!(game.board, boards[1]);
+        assert!(history.redo(&mut game));
+        assert_eq!(game.board, boards[2]);
+
+        // a new placement can't be redone over
+        game.hard_drop();
+        history.locked(&game);
+        assert!(!history.redo(&mut game));
+        assert!(history.undo(&mut game));
+        assert_eq!(game.board, boards[2]);
+    }
+
+    #[test]
+    fn only_the_last_placements_are_kept(){
+        let (mut game, mut history, boards) = played(2, 4);
+        assert!(history.undo(&mut game));
+        assert!(history.undo(&mut game));
+        assert_eq!(game.board, boards[2]);
+        assert!(!history.undo(&mut game));
+
+        let (mut game, mut history, boards) = played(0, 2);
+        assert!(!history.undo(&mut game));
+        assert_eq!(game.board, boards[2]);
+    }
+
+    #[test]
+    fn the_clock_keeps_running(){
+        let (mut game, mut history, _) = played(5, 2);
+        for _ in 0..30{
+            game.update();
+        }
+        let frames = game.frames();
+        assert!(history.undo(&mut game));
+        assert_eq!(game.frames(), frames);
+    }
+}
@@ -0,0 +1,38 @@
+//! Tetris engine without any frontend.
+//!
+//! A [`Game`] is built from [`Rules`] and a seed, and advanced one frame at a time
+//! with [`Game::update`], at 60 frames per second. [`UserControl`] turns held keys into
+//! moves with the player's [`Handling`] (DAS, ARR, soft drop factor, IRS and IHS),
+//! a frontend only has to map its keys to an [`Action`]:
+//!
+//! ```
+//! use tetris::{Action, Game, Handling, Rules, UserControl};
+//!
+//! let mut game = Game::new(Rules::new(), 1234);
+//! let mut controls = UserControl::new(Handling::new());
+//! controls.action(&mut game, Action::DROP, true);
+//! controls.action(&mut game, Action::DROP, false);
+//! controls.update(&mut game);
+//! assert!(game.top_out().is_none());
+//! ```
+//!
+//! Bots can skip [`UserControl`] and call [`Game::move_piece`], [`Game::rotate`],
+//! [`Game::hold_piece`] and [`Game::hard_drop`] directly.
+
+
+pub mod attack;
+pub mod fumen;
+pub mod garbage;
+pub mod gravity;
+pub mod history;
+pub mod mode;
+pub mod randomizer;
+pub mod records;
+pub mod replay;
+pub mod rotation;
+pub mod save;
+pub mod tetris_engine;
+pub mod user_controls;
+
+pub use tetris_engine::{Game, LockResult, Phase, Rules, TopOut};
+pub use user_controls::{Action, Handling, UserControl};
@@ -0,0 +1,121 @@
+use crate::tetris_engine::Game;
+
+/// the engine runs at this rate, frames counts are turned into times with it
+pub const FRAMES_PER_SECOND : u32 = 60;
+
+/// sprint targets, in lines
+pub const SPRINT_LINES : [u32; 4] = [20, 40, 100, 1000];
+/// a sprint split is taken every time this many more lines are cleared
+pub const SPLIT_LINES : u32 = 10;
+
+pub const BLITZ_FRAMES : u32 = 2 * 60 * FRAMES_PER_SECOND;
+pub const ULTRA_FRAMES : u32 = 3 * 60 * FRAMES_PER_SECOND;
+
+// lines per level, blitz speeds up faster
+const LEVEL_LINES : u32 = 10;
+const BLITZ_LEVEL_LINES : u32 = 5;
+
+/// what the game is played for
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Mode {
+    /// endless, until top out
+    MARATHON,
+    /// clear the given number of lines as fast as possible
+    SPRINT(u32),
+    /// best score in 2 minutes, levels come twice as fast
+    BLITZ,
+    /// best score in 3 minutes
+    ULTRA,
+}
+
+impl Mode {
+    pub fn from_name(name : &str) -> Option<Self>{
+        match name {
+            "marathon" => Some(Mode::MARATHON),
+            "blitz" => Some(Mode::BLITZ),
+            "ultra" => Some(Mode::ULTRA),
+            _ => {
+                let lines = name.strip_prefix("sprint")?.parse().ok()?;
+                SPRINT_LINES.contains(&lines).then_some(Mode::SPRINT(lines))
+            },
+        }
+    }
+
+    pub fn name(&self) -> String{
+        match self {
+            Mode::MARATHON => "marathon".to_string(),
+            Mode::SPRINT(lines) => format!("sprint{}", lines),
+            Mode::BLITZ => "blitz".to_string(),
+            Mode::ULTRA => "ultra".to_string(),
+        }
+    }
+
+    /// frames the game lasts, None when it only ends on top out or on its goal
+    pub fn time_limit(&self) -> Option<u32>{
+        match self {
+            Mode::BLITZ => Some(BLITZ_FRAMES),
+            Mode::ULTRA => Some(ULTRA_FRAMES),
+            Mode::MARATHON | Mode::SPRINT(_) => None,
+        }
+    }
+
+    /// the level after `lines` cleared lines, it sets gravity and multiplies the score
+    pub fn level(&self, lines : u32) -> u32{
+        match self {
+            Mode::BLITZ => lines / BLITZ_LEVEL_LINES,
+            _ => lines / LEVEL_LINES,
+        }
+    }
+}
+
+/// progress of a sprint, times are in frames since the start
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Sprint {
+    pub lines : u32,
+    /// frame each multiple of SPLIT_LINES was reached on
+    pub splits : Vec<u32>,
+    pub finish : Option<u32>,
+}
+
+impl Sprint {
+    pub fn new(lines : u32) -> Self{
+        Self{
+            lines,
+            splits : Vec::new(),
+            finish : None,
+        }
+    }
+
+    /// call after every frame with the number of frames played so far
+    pub fn update(&mut self, game : &Game, frame : u32){
+        if self.finish.is_some(){
+            return;
+        }
+        let lines = game.lines_cleared.min(self.lines);
+        // undo can take lines back
+        self.splits.truncate((lines / SPLIT_LINES) as usize);
+        while (self.splits.len() as u32) < lines / SPLIT_LINES{
+            self.splits.push(frame);
+        }
+        if lines >= self.lines{
+            self.finish = Some(frame);
+        }
+    }
+
+    pub fn remaining(&self, game : &Game) -> u32{
+        self.lines.saturating_sub(game.lines_cleared)
+    }
+}
+
+/// frames as `m:ss.mmm`
+pub fn format_time(frames : u32) -> String{
+    let millis = frames as u64 * 1000 / FRAMES_PER_SECOND as u64;
+    format!("{}:{:02}.{:03}", millis / 60000, millis / 1000 % 60, millis % 1000)
+}
+
+/// the difference between two times, `+1.250` when `frames` is behind `best`
+pub fn format_delta(frames : u32, best : u32) -> String{
+    let millis = (frames as i64 - best as i64) * 1000 / FRAMES_PER_SECOND as i64;
+    let sign = if millis < 0 {'-'} else {'+'};
+    format!("{}{}.{:03}", sign, millis.abs() / 1000, millis.abs() % 1000)
+}
@@ -0,0 +1,345 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use rand::seq::SliceRandom;
+
+use crate::tetris_engine::{piece_index, PIECES};
+
+// piece indices in PIECES
+const I : usize = 0;
+const J : usize = 1;
+const L : usize = 2;
+const S : usize = 4;
+const T : usize = 5;
+const Z : usize = 6;
+
+const PIECE_COUNT : usize = 7;
+
+/// seed and position in the stream, enough to continue where it was
+pub(crate) fn rng_state(rng : &ChaCha12Rng) -> String{
+    let seed : String = rng.get_seed().iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}:{}", seed, rng.get_word_pos())
+}
+
+pub(crate) fn restore_rng(state : &str) -> Result<ChaCha12Rng, String>{
+    let error = || format!("bad rng state: {}", state);
+    let (seed, position) = state.split_once(':').ok_or_else(error)?;
+    if seed.len() != 64{
+        return Err(error());
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate(){
+        *byte = u8::from_str_radix(&seed[i * 2..i * 2 + 2], 16).map_err(|_| error())?;
+    }
+    let mut rng = ChaCha12Rng::from_seed(bytes);
+    rng.set_word_pos(position.parse().map_err(|_| error())?);
+    Ok(rng)
+}
+
+/// pieces as their names, `-` for none
+pub(crate) fn piece_names(pieces : &[usize]) -> String{
+    if pieces.is_empty(){
+        return "-".to_string();
+    }
+    pieces.iter().map(|piece| PIECES[*piece].name).collect()
+}
+
+pub(crate) fn parse_piece_names(names : &str) -> Result<Vec<usize>, String>{
+    if names == "-"{
+        return Ok(Vec::new());
+    }
+    names.chars()
+        .map(|name| piece_index(name).ok_or(format!("bad piece: {}", name)))
+        .collect()
+}
+
+fn split_state<const N : usize>(state : &str) -> Result<[&str; N], String>{
+    let parts : Vec<&str> = state.split(' ').collect();
+    parts.try_into().map_err(|_| format!("bad randomizer state: {}", state))
+}
+
+/// generates the sequence of pieces, as indices in PIECES
+pub trait Randomizer : RandomizerClone {
+    fn next(&mut self) -> usize;
+
+    /// the internal state as text, for save files
+    fn state(&self) -> String;
+
+    /// puts back a saved state, on a randomizer built from the same kind
+    fn restore(&mut self, state : &str) -> Result<(), String>;
+}
+
+/// lets a game be cloned with its randomizer state, for undo
+pub trait RandomizerClone {
+    fn clone_box(&self) -> Box<dyn Randomizer>;
+}
+
+impl<T : Randomizer + Clone + 'static> RandomizerClone for T {
+    fn clone_box(&self) -> Box<dyn Randomizer>{
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Self{
+        self.clone_box()
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RandomizerKind {BAG_7, BAG_14, RANDOM, NES, TGM, FIXED(Vec<usize>)}
+
+impl RandomizerKind {
+    /// fails on an empty fixed sequence
+    pub fn build(&self, seed : u64) -> Result<Box<dyn Randomizer>, String>{
+        let rng = ChaCha12Rng::seed_from_u64(seed);
+        Ok(match self {
+            RandomizerKind::BAG_7 => Box::new(Bag::new(rng, 1)),
+            RandomizerKind::BAG_14 => Box::new(Bag::new(rng, 2)),
+            RandomizerKind::RANDOM => Box::new(PureRandom { rng }),
+            RandomizerKind::NES => Box::new(Nes { rng, last : PIECE_COUNT }),
+            RandomizerKind::TGM => Box::new(Tgm::new(rng)),
+            RandomizerKind::FIXED(sequence) => Box::new(FixedSequence::new(sequence.clone())?),
+        })
+    }
+
+    pub fn from_name(name : &str) -> Option<Self>{
+        match name {
+            "7bag" => Some(RandomizerKind::BAG_7),
+            "14bag" => Some(RandomizerKind::BAG_14),
+            "random" => Some(RandomizerKind::RANDOM),
+            "nes" => Some(RandomizerKind::NES),
+            "tgm" => Some(RandomizerKind::TGM),
+            _ => {
+                let sequence = name.strip_prefix("fixed ")?;
+                let pieces : Option<Vec<usize>> = sequence.chars().map(piece_index).collect();
+                pieces.filter(|pieces| !pieces.is_empty()).map(RandomizerKind::FIXED)
+            },
+        }
+    }
+
+    pub fn name(&self) -> String{
+        match self {
+            RandomizerKind::BAG_7 => "7bag".to_string(),
+            RandomizerKind::BAG_14 => "14bag".to_string(),
+            RandomizerKind::RANDOM => "random".to_string(),
+            RandomizerKind::NES => "nes".to_string(),
+            RandomizerKind::TGM => "tgm".to_string(),
+            RandomizerKind::FIXED(pieces) => format!("fixed {}", piece_names(pieces)),
+        }
+    }
+}
+
+/// every piece `copies` times per bag
+#[derive(Clone)]
+pub struct Bag {
+    queue : Vec<usize>,
+    copies : usize,
+    rng : ChaCha12Rng,
+}
+
+impl Bag {
+    pub fn new(rng : ChaCha12Rng, copies : usize) -> Self{
+        Self{
+            queue : Vec::new(),
+            copies,
+            rng,
+        }
+    }
+
+    fn refill(&mut self){
+        let mut indices: Vec<usize> = (0..PIECE_COUNT * self.copies).map(|i| i % PIECE_COUNT).collect();
+        indices.shuffle(&mut self.rng);
+        self.queue.extend(indices);
+    }
+}
+
+impl Randomizer for Bag {
+    fn next(&mut self) -> usize{
+        if self.queue.is_empty() {
+            self.refill();
+        }
+        self.queue.remove(0)
+    }
+
+    fn state(&self) -> String{
+        format!("{} {}", piece_names(&self.queue), rng_state(&self.rng))
+    }
+
+    fn restore(&mut self, state : &str) -> Result<(), String>{
+        let [queue, rng] = split_state(state)?;
+        self.queue = parse_piece_names(queue)?;
+        self.rng = restore_rng(rng)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct PureRandom {
+    rng : ChaCha12Rng,
+}
+
+impl Randomizer for PureRandom {
+    fn next(&mut self) -> usize{
+        self.rng.gen_range(0..PIECE_COUNT)
+    }
+
+    fn state(&self) -> String{
+        rng_state(&self.rng)
+    }
+
+    fn restore(&mut self, state : &str) -> Result<(), String>{
+        self.rng = restore_rng(state)?;
+        Ok(())
+    }
+}
+
+/// NES: roll an 8 sided die, reroll once on the extra side or on a repeat
+#[derive(Clone)]
+pub struct Nes {
+    rng : ChaCha12Rng,
+    last : usize,
+}
+
+impl Randomizer for Nes {
+    fn next(&mut self) -> usize{
+        let mut piece = self.rng.gen_range(0..PIECE_COUNT + 1);
+        if piece == PIECE_COUNT || piece == self.last{
+            piece = self.rng.gen_range(0..PIECE_COUNT);
+        }
+        self.last = piece;
+        piece
+    }
+
+    fn state(&self) -> String{
+        format!("{} {}", self.last, rng_state(&self.rng))
+    }
+
+    fn restore(&mut self, state : &str) -> Result<(), String>{
+        let [last, rng] = split_state(state)?;
+        self.last = last.parse().ok().filter(|last| *last <= PIECE_COUNT).ok_or(format!("bad randomizer state: {}", state))?;
+        self.rng = restore_rng(rng)?;
+        Ok(())
+    }
+}
+
+const TGM_HISTORY : usize = 4;
+const TGM_REROLLS : usize = 6;
+
+/// TGM: 4 pieces history, a roll and up to 6 rerolls to find a piece not in it
+#[derive(Clone)]
+pub struct Tgm {
+    rng : ChaCha12Rng,
+    history : [usize; TGM_HISTORY],
+    first : bool,
+}
+
+impl Tgm {
+    pub fn new(rng : ChaCha12Rng) -> Self{
+        Self{
+            rng,
+            history : [Z, S, S, Z],
+            first : true,
+        }
+    }
+}
+
+impl Randomizer for Tgm {
+    fn next(&mut self) -> usize{
+        let piece = if self.first {
+            // the first piece is never an S, Z or O
+            self.first = false;
+            *[I, J, L, T].choose(&mut self.rng).unwrap()
+        } else {
+            let mut piece = self.rng.gen_range(0..PIECE_COUNT);
+            for _ in 0..TGM_REROLLS{
+                if !self.history.contains(&piece){
+                    break;
+                }
+                piece = self.rng.gen_range(0..PIECE_COUNT);
+            }
+            piece
+        };
+        self.history.rotate_left(1);
+        self.history[TGM_HISTORY - 1] = piece;
+        piece
+    }
+
+    fn state(&self) -> String{
+        format!("{} {} {}", piece_names(&self.history), self.first, rng_state(&self.rng))
+    }
+
+    fn restore(&mut self, state : &str) -> Result<(), String>{
+        let [history, first, rng] = split_state(state)?;
+        self.history = parse_piece_names(history)?.try_into().map_err(|_| format!("bad randomizer state: {}", state))?;
+        self.first = first.parse().map_err(|_| format!("bad randomizer state: {}", state))?;
+        self.rng = restore_rng(rng)?;
+        Ok(())
+    }
+}
+
+/// repeats the given sequence forever
+#[derive(Clone)]
+pub struct FixedSequence {
+    sequence : Vec<usize>,
+    position : usize,
+}
+
+impl FixedSequence {
+    pub fn new(sequence : Vec<usize>) -> Result<Self, String>{
+        if sequence.is_empty(){
+            return Err("a fixed sequence needs at least one piece".to_string());
+        }
+        Ok(Self{
+            sequence,
+            position : 0,
+        })
+    }
+}
+
+impl Randomizer for FixedSequence {
+    fn next(&mut self) -> usize{
+        let piece = self.sequence[self.position];
+        self.position = (self.position + 1) % self.sequence.len();
+        piece
+    }
+
+    fn state(&self) -> String{
+        self.position.to_string()
+    }
+
+    fn restore(&mut self, state : &str) -> Result<(), String>{
+        self.position = state.parse().ok().filter(|position| *position < self.sequence.len()).ok_or(format!("bad randomizer state: {}", state))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_fixed_sequence_is_rejected(){
+        assert!(FixedSequence::new(Vec::new()).is_err());
+        assert!(RandomizerKind::FIXED(Vec::new()).build(1).is_err());
+        assert_eq!(RandomizerKind::from_name("fixed "), None);
+    }
+
+    #[test]
+    fn fixed_sequence_repeats(){
+        let mut randomizer = RandomizerKind::from_name("fixed TIO").unwrap().build(1).unwrap();
+        let pieces : Vec<usize> = (0..6).map(|_| randomizer.next()).collect();
+        assert_eq!(piece_names(&pieces), "TIOTIO");
+    }
+
+    #[test]
+    fn tgm_avoids_its_history(){
+        // with a roll and 6 rerolls a piece from the last 4 is rare, it must not be the norm
+        let mut randomizer = RandomizerKind::TGM.build(7).unwrap();
+        let pieces : Vec<usize> = (0..1000).map(|_| randomizer.next()).collect();
+        let repeats = pieces.windows(TGM_HISTORY + 1)
+            .filter(|window| window[..TGM_HISTORY].contains(&window[TGM_HISTORY]))
+            .count();
+        assert!(repeats < 50, "{} repeats", repeats);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::mode::{Mode, Sprint};
+
+const HEADER : &str = "tetris records 1";
+
+/// personal bests, kept in a file between games
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Records {
+    // by target lines
+    sprints : BTreeMap<u32, Sprint>,
+    // best score of the timed modes, by mode name
+    scores : BTreeMap<String, u32>,
+}
+
+impl Default for Records {
+    fn default() -> Self{
+        Records::new()
+    }
+}
+
+impl Records {
+    pub fn new() -> Self{
+        Self{
+            sprints : BTreeMap::new(),
+            scores : BTreeMap::new(),
+        }
+    }
+
+    /// the fastest finished sprint to that many lines
+    pub fn best_sprint(&self, lines : u32) -> Option<&Sprint>{
+        self.sprints.get(&lines)
+    }
+
+    /// keeps the sprint if it is finished and faster than the best, true when it is
+    pub fn add_sprint(&mut self, sprint : &Sprint) -> bool{
+        let Some(finish) = sprint.finish else {
+            return false;
+        };
+        let best = self.best_sprint(sprint.lines).and_then(|best| best.finish);
+        if best.is_some_and(|best| best <= finish){
+            return false;
+        }
+        self.sprints.insert(sprint.lines, sprint.clone());
+        true
+    }
+
+    pub fn best_score(&self, mode : Mode) -> Option<u32>{
+        self.scores.get(&mode.name()).copied()
+    }
+
+    /// keeps the score if it beats the best of its mode, true when it does
+    pub fn add_score(&mut self, mode : Mode, score : u32) -> bool{
+        if self.best_score(mode).is_some_and(|best| best >= score){
+            return false;
+        }
+        self.scores.insert(mode.name(), score);
+        true
+    }
+
+    pub fn to_text(&self) -> String{
+        let mut text = String::new();
+        writeln!(text, "{}", HEADER).unwrap();
+        // sprint LINES FINISH SPLITS...
+        for sprint in self.sprints.values(){
+            let splits : Vec<String> = sprint.splits.iter().map(|split| split.to_string()).collect();
+            writeln!(text, "sprint {} {} {}", sprint.lines, sprint.finish.unwrap_or(0), splits.join(" ")).unwrap();
+        }
+        // score MODE SCORE
+        for (mode, score) in &self.scores{
+            writeln!(text, "score {} {}", mode, score).unwrap();
+        }
+        text
+    }
+
+    pub fn from_text(text : &str) -> Result<Self, String>{
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER){
+            return Err("not a records file".to_string());
+        }
+        let mut records = Records::new();
+        for line in lines{
+            let error = || format!("bad line: {}", line);
+            let values : Vec<&str> = line.split_whitespace().collect();
+            if let ["score", mode, score] = values.as_slice(){
+                let mode = Mode::from_name(mode).filter(|mode| mode.time_limit().is_some()).ok_or_else(error)?;
+                records.scores.insert(mode.name(), score.parse().map_err(|_| error())?);
+                continue;
+            }
+            let numbers = values.iter().skip(1)
+                .map(|value| value.parse::<u32>())
+                .collect::<Result<Vec<u32>, _>>()
+                .map_err(|_| error())?;
+            match (values.first(), numbers.as_slice()) {
+                (Some(&"sprint"), [lines, finish, splits @ ..]) => {
+                    records.sprints.insert(*lines, Sprint{
+                        lines : *lines,
+                        splits : splits.to_vec(),
+                        finish : Some(*finish),
+                    });
+                },
+                _ => return Err(error()),
+            }
+        }
+        Ok(records)
+    }
+
+    pub fn save(&self, path : &str) -> std::io::Result<()>{
+        std::fs::write(path, self.to_text())
+    }
+
+    /// no file yet means no records
+    pub fn load(path : &str) -> Result<Self, String>{
+        match std::fs::read_to_string(path) {
+            Ok(text) => Records::from_text(&text),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Records::new()),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Records{
+        let mut records = Records::new();
+        records.add_sprint(&Sprint{lines : 40, splits : vec![600, 1300, 2000, 2700], finish : Some(2750)});
+        records.add_sprint(&Sprint{lines : 20, splits : vec![500], finish : Some(1100)});
+        records.add_score(Mode::BLITZ, 120000);
+        records.add_score(Mode::ULTRA, 300000);
+        records
+    }
+
+    #[test]
+    fn round_trip(){
+        let text = records().to_text();
+        assert_eq!(Records::from_text(&text), Ok(records()));
+        assert_eq!(Records::from_text(&format!("{}\n", HEADER)), Ok(Records::new()));
+    }
+
+    #[test]
+    fn only_better_records_are_kept(){
+        let mut records = records();
+        assert!(!records.add_sprint(&Sprint{lines : 40, splits : Vec::new(), finish : Some(2750)}));
+        assert!(!records.add_sprint(&Sprint{lines : 40, splits : Vec::new(), finish : None}));
+        assert!(records.add_sprint(&Sprint{lines : 40, splits : Vec::new(), finish : Some(2749)}));
+        assert!(!records.add_score(Mode::BLITZ, 120000));
+        assert!(records.add_score(Mode::BLITZ, 120001));
+        assert_eq!(records.best_score(Mode::BLITZ), Some(120001));
+    }
+
+    #[test]
+    fn malformed_records_are_rejected(){
+        let bad = [
+            "",
+            "tetris records 2",
+            "tetris records 1\nsprint 40",
+            "tetris records 1\nsprint forty 2750",
+            "tetris records 1\nsprint 40 -1",
+            "tetris records 1\nscore marathon 100",
+            "tetris records 1\nscore blitz",
+            "tetris records 1\nscore blitz lots",
+            "tetris records 1\nbest 40 2750",
+        ];
+        for text in bad{
+            assert!(Records::from_text(text).is_err(), "{}", text);
+        }
+    }
+}
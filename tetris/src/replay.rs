@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::attack::AttackTable;
+use crate::fumen;
+use crate::gravity::GravityCurve;
+use crate::history::History;
+use crate::mode::Mode;
+use crate::randomizer::RandomizerKind;
+use crate::rotation::RotationSystemKind;
+use crate::tetris_engine::{Game, LockReset, LockResult, Rules, SoftDropFactor};
+use crate::user_controls::{Action, DirectionPolicy, Handling, UserControl};
+
+const HEADER : &str = "tetris replay 1";
+
+/// something that happened to the game during a frame, before its update
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ReplayEvent {
+    /// a key pressed or released
+    ACTION(Action, bool),
+    /// garbage lines received
+    GARBAGE(u32),
+}
+
+impl ReplayEvent {
+    /// the only way inputs reach a recorded game, so playback does exactly the same
+    pub fn apply(&self, game : &mut Game, controls : &mut UserControl, history : &mut History) -> Option<LockResult>{
+        let result = match *self {
+            ReplayEvent::ACTION(Action::UNDO, true) => {
+                history.undo(game);
+                None
+            },
+            ReplayEvent::ACTION(Action::REDO, true) => {
+                history.redo(game);
+                None
+            },
+            ReplayEvent::ACTION(action, pressed) => controls.action(game, action, pressed),
+            ReplayEvent::GARBAGE(lines) => {
+                game.receive_garbage(lines, None);
+                None
+            },
+        };
+        if result.is_some(){
+            history.locked(game);
+        }
+        result
+    }
+}
+
+/// everything needed to play a game again: seed, rules, handling and every input with its frame
+pub struct Replay {
+    pub seed : u64,
+    pub rules : Rules,
+    pub handling : Handling,
+    pub events : Vec<(u32, ReplayEvent)>,
+    /// frames the game lasted
+    pub frames : u32,
+    /// starting position the game was loaded from
+    pub fumen : Option<String>,
+}
+
+impl Replay {
+    pub fn new(seed : u64, rules : Rules, handling : Handling) -> Self{
+        Self{
+            seed,
+            rules,
+            handling,
+            events : Vec::new(),
+            frames : 0,
+            fumen : None,
+        }
+    }
+
+    /// a new game with the replay's seed, rules and starting position
+    pub fn start(&self) -> Result<Game, String>{
+        self.rules.validate()?;
+        let mut game = Game::new(self.rules.clone(), self.seed);
+        if let Some(fumen) = &self.fumen{
+            fumen::load_into(&mut game, &fumen::decode(fumen)?)?;
+        }
+        Ok(game)
+    }
+
+    /// played from the start with the standard rules, so it can set a record,
+    /// undo is off in those and its key does nothing
+    pub fn counts_for_records(&self) -> bool{
+        self.fumen.is_none() && self.rules.is_standard()
+    }
+
+    /// `frame` is the number of updates done before the event
+    pub fn record(&mut self, frame : u32, event : ReplayEvent){
+        self.events.push((frame, event));
+        self.frames = self.frames.max(frame);
+    }
+
+    pub fn to_text(&self) -> String{
+        let mut text = String::new();
+        writeln!(text, "{}", HEADER).unwrap();
+        writeln!(text, "seed {}", self.seed).unwrap();
+        writeln!(text, "frames {}", self.frames).unwrap();
+        write_rules(&mut text, &self.rules);
+        write_handling(&mut text, &self.handling);
+        writeln!(text, "fumen {}", self.fumen.as_deref().unwrap_or("-")).unwrap();
+        writeln!(text, "events").unwrap();
+        for (frame, event) in &self.events{
+            match event {
+                ReplayEvent::ACTION(action, pressed) => writeln!(text, "{} {} {}", frame, if *pressed {"press"} else {"release"}, action.name()).unwrap(),
+                ReplayEvent::GARBAGE(lines) => writeln!(text, "{} garbage {}", frame, lines).unwrap(),
+            }
+        }
+        text
+    }
+
+    pub fn from_text(text : &str) -> Result<Self, String>{
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER){
+            return Err("not a replay".to_string());
+        }
+        let mut fields = HashMap::new();
+        for line in lines.by_ref(){
+            if line == "events"{
+                break;
+            }
+            let (key, value) = line.split_once(' ').ok_or(format!("bad line: {}", line))?;
+            fields.insert(key, value);
+        }
+        let mut replay = Replay::new(
+            parse(&fields, "seed")?,
+            read_rules(&fields)?,
+            read_handling(&fields)?,
+        );
+        let fumen = fields.get("fumen").ok_or("missing fumen")?;
+        replay.fumen = if *fumen == "-" {None} else {Some(fumen.to_string())};
+        replay.start()?;
+        for line in lines{
+            let event : Vec<&str> = line.split(' ').collect();
+            let (frame, event) = match event.as_slice() {
+                [frame, "press", action] | [frame, "release", action] => (
+                    frame,
+                    ReplayEvent::ACTION(
+                        Action::from_name(action).ok_or(format!("bad action: {}", action))?,
+                        event[1] == "press",
+                    ),
+                ),
+                [frame, "garbage", lines] => (
+                    frame,
+                    ReplayEvent::GARBAGE(lines.parse().map_err(|_| format!("bad line: {}", line))?),
+                ),
+                _ => return Err(format!("bad line: {}", line)),
+            };
+            replay.record(frame.parse().map_err(|_| format!("bad line: {}", line))?, event);
+        }
+        replay.frames = replay.frames.max(parse(&fields, "frames")?);
+        Ok(replay)
+    }
+
+    pub fn save(&self, path : &str) -> std::io::Result<()>{
+        std::fs::write(path, self.to_text())
+    }
+
+    pub fn load(path : &str) -> Result<Self, String>{
+        let text = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        Replay::from_text(&text)
+    }
+}
+
+/// plays a replay back one frame at a time
+pub struct Playback {
+    replay : Replay,
+    game : Game,
+    controls : UserControl,
+    history : History,
+    frame : u32,
+    // index of the next event to apply
+    next : usize,
+    pub last_lock : Option<LockResult>,
+}
+
+impl Playback {
+    /// the replay's fumen has to load, `Replay::start` tells if it does
+    pub fn new(replay : Replay) -> Self{
+        let game = replay.start().unwrap();
+        let controls = UserControl::new(replay.handling.clone());
+        let mut history = History::new(replay.rules.undo_limit);
+        history.start(&game);
+        Self{
+            replay,
+            game,
+            controls,
+            history,
+            frame : 0,
+            next : 0,
+            last_lock : None,
+        }
+    }
+
+    pub fn game(&self) -> &Game{
+        &self.game
+    }
+
+    pub fn frame(&self) -> u32{
+        self.frame
+    }
+
+    pub fn frames(&self) -> u32{
+        self.replay.frames
+    }
+
+    pub fn finished(&self) -> bool{
+        self.frame >= self.replay.frames || self.game.top_out().is_some() || self.game.time_up()
+    }
+
+    /// applies the events of the current frame, then updates the game
+    pub fn step(&mut self){
+        if self.finished(){
+            return;
+        }
+        while let Some((frame, event)) = self.replay.events.get(self.next){
+            if *frame > self.frame{
+                break;
+            }
+            self.next += 1;
+            if let Some(result) = event.apply(&mut self.game, &mut self.controls, &mut self.history){
+                self.last_lock = Some(result);
+            }
+        }
+        if let Some(result) = self.controls.update(&mut self.game){
+            self.history.locked(&self.game);
+            self.last_lock = Some(result);
+        }
+        self.frame += 1;
+    }
+
+    /// going back plays the game again from the start, it is deterministic
+    pub fn seek(&mut self, frame : u32){
+        if frame < self.frame{
+            self.game = self.replay.start().unwrap();
+            self.controls = UserControl::new(self.replay.handling.clone());
+            self.history.start(&self.game);
+            self.frame = 0;
+            self.next = 0;
+            self.last_lock = None;
+        }
+        while self.frame < frame && !self.finished(){
+            self.step();
+        }
+    }
+}
+
+pub(crate) fn parse<T : std::str::FromStr>(fields : &HashMap<&str, &str>, key : &str) -> Result<T, String>{
+    let value = fields.get(key).ok_or(format!("missing {}", key))?;
+    value.parse().map_err(|_| format!("bad {}: {}", key, value))
+}
+
+fn parse_name<T>(fields : &HashMap<&str, &str>, key : &str, from_name : fn(&str) -> Option<T>) -> Result<T, String>{
+    let value = fields.get(key).ok_or(format!("missing {}", key))?;
+    from_name(value).ok_or(format!("bad {}: {}", key, value))
+}
+
+pub(crate) fn write_rules(text : &mut String, rules : &Rules){
+    writeln!(text, "width {}", rules.width).unwrap();
+    writeln!(text, "height {}", rules.height).unwrap();
+    writeln!(text, "garbage_messiness {}", rules.garbage_messiness).unwrap();
+    writeln!(text, "lock_delay {}", rules.lock_delay).unwrap();
+    writeln!(text, "lock_reset {}", rules.lock_reset.name()).unwrap();
+    writeln!(text, "lock_reset_limit {}", rules.lock_reset_limit).unwrap();
+    writeln!(text, "are {}", rules.are).unwrap();
+    writeln!(text, "line_are {}", rules.line_are).unwrap();
+    writeln!(text, "line_clear_delay {}", rules.line_clear_delay).unwrap();
+    writeln!(text, "garbage_cap {}", rules.garbage_cap).unwrap();
+    writeln!(text, "attack_table {}", rules.attack_table.name()).unwrap();
+    writeln!(text, "randomizer {}", rules.randomizer.name()).unwrap();
+    writeln!(text, "rotation_system {}", rules.rotation_system.name()).unwrap();
+    writeln!(text, "gravity {}", rules.gravity.name()).unwrap();
+    writeln!(text, "undo_limit {}", rules.undo_limit).unwrap();
+    writeln!(text, "mode {}", rules.mode.name()).unwrap();
+}
+
+pub(crate) fn read_rules(fields : &HashMap<&str, &str>) -> Result<Rules, String>{
+    Ok(Rules{
+        width : parse(fields, "width")?,
+        height : parse(fields, "height")?,
+        garbage_messiness : parse(fields, "garbage_messiness")?,
+        lock_delay : parse(fields, "lock_delay")?,
+        lock_reset : parse_name(fields, "lock_reset", LockReset::from_name)?,
+        lock_reset_limit : parse(fields, "lock_reset_limit")?,
+        are : parse(fields, "are")?,
+        line_are : parse(fields, "line_are")?,
+        line_clear_delay : parse(fields, "line_clear_delay")?,
+        garbage_cap : parse(fields, "garbage_cap")?,
+        attack_table : parse_name(fields, "attack_table", AttackTable::from_name)?,
+        randomizer : parse_name(fields, "randomizer", RandomizerKind::from_name)?,
+        rotation_system : parse_name(fields, "rotation_system", RotationSystemKind::from_name)?,
+        gravity : parse_name(fields, "gravity", GravityCurve::from_name)?,
+        undo_limit : parse(fields, "undo_limit")?,
+        mode : parse_name(fields, "mode", Mode::from_name)?,
+    })
+}
+
+pub(crate) fn write_handling(text : &mut String, handling : &Handling){
+    writeln!(text, "das {}", handling.das_delay).unwrap();
+    writeln!(text, "arr {}", handling.arr).unwrap();
+    writeln!(text, "sdf {}", handling.sdf.name()).unwrap();
+    writeln!(text, "irs {}", handling.irs).unwrap();
+    writeln!(text, "ihs {}", handling.ihs).unwrap();
+    writeln!(text, "das_carry {}", handling.das_carry).unwrap();
+    writeln!(text, "dcd {}", handling.dcd).unwrap();
+    writeln!(text, "direction_policy {}", handling.direction_policy.name()).unwrap();
+}
+
+pub(crate) fn read_handling(fields : &HashMap<&str, &str>) -> Result<Handling, String>{
+    Ok(Handling{
+        das_delay : parse(fields, "das")?,
+        arr : parse(fields, "arr")?,
+        sdf : parse_name(fields, "sdf", SoftDropFactor::from_name)?,
+        irs : parse(fields, "irs")?,
+        ihs : parse(fields, "ihs")?,
+        das_carry : parse(fields, "das_carry")?,
+        dcd : parse(fields, "dcd")?,
+        direction_policy : parse_name(fields, "direction_policy", DirectionPolicy::from_name)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_out_plays_back(){
+        let mut replay = Replay::new(3, Rules::new(), Handling::new());
+        let mut game = replay.start().unwrap();
+        let mut controls = UserControl::new(replay.handling.clone());
+        let mut history = History::new(0);
+        history.start(&game);
+        // hard drop every few frames until the stack reaches the top, like the frontend loop
+        let mut frames = 0;
+        while game.top_out().is_none(){
+            if frames % 5 == 0{
+                for pressed in [true, false]{
+                    let event = ReplayEvent::ACTION(Action::DROP, pressed);
+                    replay.record(frames, event);
+                    event.apply(&mut game, &mut controls, &mut history);
+                }
+            }
+            controls.update(&mut game);
+            frames += 1;
+        }
+        replay.frames = frames;
+
+        let mut playback = Playback::new(Replay::from_text(&replay.to_text()).unwrap());
+        while !playback.finished(){
+            playback.step();
+        }
+        assert_eq!(playback.frame(), frames);
+        assert!(playback.game().top_out().is_some());
+        assert_eq!(playback.game().top_out(), game.top_out());
+        assert_eq!(playback.game().board, game.board);
+    }
+
+    fn recorded() -> Replay{
+        let mut rules = Rules::new();
+        rules.mode = Mode::SPRINT(40);
+        rules.undo_limit = 5;
+        let mut replay = Replay::new(9, rules, Handling::new());
+        replay.fumen = Some("v115@vhAVPJ".to_string());
+        replay.record(0, ReplayEvent::ACTION(Action::LEFT, true));
+        replay.record(3, ReplayEvent::ACTION(Action::LEFT, false));
+        replay.record(3, ReplayEvent::GARBAGE(4));
+        replay.record(10, ReplayEvent::ACTION(Action::UNDO, true));
+        replay.frames = 20;
+        replay
+    }
+
+    #[test]
+    fn round_trip(){
+        let text = recorded().to_text();
+        let replay = Replay::from_text(&text).unwrap();
+        assert_eq!(replay.events, recorded().events);
+        assert_eq!(replay.frames, 20);
+        assert_eq!(replay.fumen, recorded().fumen);
+        assert_eq!(replay.rules.mode, Mode::SPRINT(40));
+        assert_eq!(replay.to_text(), text);
+    }
+
+    #[test]
+    fn only_standard_games_count_for_records(){
+        let mut replay = Replay::new(9, Rules::new(), Handling::new());
+        replay.rules.mode = Mode::BLITZ;
+        replay.record(2, ReplayEvent::ACTION(Action::DROP, true));
+        assert!(replay.counts_for_records());
+
+        let mut loaded = Replay::new(9, Rules::new(), Handling::new());
+        loaded.fumen = Some("v115@vhAVPJ".to_string());
+        assert!(!loaded.counts_for_records());
+
+        let mut rules = Rules::new();
+        rules.gravity = GravityCurve::from_name("20g").unwrap();
+        assert!(!Replay::new(9, rules, Handling::new()).counts_for_records());
+
+        replay.record(5, ReplayEvent::ACTION(Action::UNDO, true));
+        assert!(replay.counts_for_records());
+        replay.rules.undo_limit = 3;
+        assert!(!replay.counts_for_records());
+    }
+
+    #[test]
+    fn malformed_replays_are_rejected(){
+        let text = recorded().to_text();
+        let bad = [
+            text.replacen("tetris replay 1", "tetris replay 2", 1),
+            text.replacen("seed 9", "seed nine", 1),
+            text.replacen("width 10", "width 200", 1),
+            text.replacen("randomizer 7bag", "randomizer fixed", 1),
+            text.replacen("mode sprint40", "mode sprint41", 1),
+            text.replacen("mode sprint40\n", "", 1),
+            text.replacen("undo_limit ", "undo ", 1),
+            text.replacen("fumen v115@vhAVPJ\n", "", 1),
+            text.replacen("fumen v115@vhAVPJ", "fumen v115@!!", 1),
+            text.replacen("3 garbage 4", "3 garbage four", 1),
+            text.replacen("0 press left", "0 press jump", 1),
+            text.replacen("0 press left", "zero press left", 1),
+            text.replacen("0 press left", "0 hold left", 1),
+            text.replacen("frames 20", "frames", 1),
+        ];
+        for text in bad{
+            assert!(Replay::from_text(&text).is_err(), "{}", text);
+        }
+    }
+}
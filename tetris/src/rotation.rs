@@ -0,0 +1,368 @@
+use crate::tetris_engine::{piece_index, Piece, Tetromino};
+
+/// shapes and kicks are both in piece coordinates, x to the right and y up
+pub trait RotationSystem : RotationSystemClone {
+    fn tetromino(&self, piece : &Piece, rotation : usize) -> Tetromino;
+
+    // offsets to try in order when rotating from `start` to `end`
+    fn kicks(&self, piece : &Piece, start : usize, end : usize) -> Vec<(i8, i8)>;
+
+    // called when the rotation without any kick doesn't fit, `blocked` tells which
+    // cells of the rotated piece are taken
+    fn can_kick(&self, _piece : &Piece, _tetromino : &Tetromino, _blocked : &dyn Fn((i8, i8)) -> bool) -> bool{
+        true
+    }
+}
+
+pub trait RotationSystemClone {
+    fn clone_box(&self) -> Box<dyn RotationSystem>;
+}
+
+impl<T : RotationSystem + Clone + 'static> RotationSystemClone for T {
+    fn clone_box(&self) -> Box<dyn RotationSystem>{
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn RotationSystem> {
+    fn clone(&self) -> Self{
+        self.clone_box()
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RotationSystemKind {SRS, SRS_PLUS, ARS, NRS}
+
+impl RotationSystemKind {
+    pub fn build(&self) -> Box<dyn RotationSystem>{
+        match self {
+            RotationSystemKind::SRS => Box::new(Srs { plus : false }),
+            RotationSystemKind::SRS_PLUS => Box::new(Srs { plus : true }),
+            RotationSystemKind::ARS => Box::new(Ars {}),
+            RotationSystemKind::NRS => Box::new(Nrs {}),
+        }
+    }
+
+    pub fn from_name(name : &str) -> Option<Self>{
+        match name {
+            "srs" => Some(RotationSystemKind::SRS),
+            "srs+" => Some(RotationSystemKind::SRS_PLUS),
+            "ars" => Some(RotationSystemKind::ARS),
+            "nrs" => Some(RotationSystemKind::NRS),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str{
+        match self {
+            RotationSystemKind::SRS => "srs",
+            RotationSystemKind::SRS_PLUS => "srs+",
+            RotationSystemKind::ARS => "ars",
+            RotationSystemKind::NRS => "nrs",
+        }
+    }
+}
+
+static OFFSET_DATA : [[(i8,i8); 5]; 4] = [
+    [(0,0), (0,0), (0,0), (0,0), (0,0)],
+    [(0,0), (1,0), (1, -1), (0, 2), (1,2)],
+    [(0,0), (0,0), (0,0), (0,0), (0,0)],
+    [(0,0), (-1,0), (-1,-1), (0,2), (-1,2)]
+];
+
+static I_OFFSET_DATA : [[(i8,i8); 5]; 4] = [
+    [(0,0), (-1,0), (2,0), (-1,0), (2,0)],
+    [(-1,0), (0,0), (0,0), (0,1), (0,-2)],
+    [(-1,1), (1,1), (-2,1), (1,0), (-2,0)],
+    [(0,1), (0,1), (0,1), (0,-1), (0,2)] 
+];
+
+static O_OFFESET_DATA : [(i8,i8); 4] = [
+    (0,0), (0,-1), (-1,-1), (-1,0)
+];
+
+// TETR.IO 180 kicks, indexed by the starting rotation
+static SRS_PLUS_180 : [[(i8,i8); 6]; 4] = [
+    [(0,0), (0,1), (1,1), (-1,1), (1,0), (-1,0)],
+    [(0,0), (1,0), (1,2), (1,1), (0,2), (0,1)],
+    [(0,0), (0,-1), (-1,-1), (1,-1), (-1,0), (1,0)],
+    [(0,0), (-1,0), (-1,2), (-1,1), (0,2), (0,1)]
+];
+
+// TETR.IO symmetric I kicks, indexed by the starting rotation, then clockwise / counter clockwise
+static SRS_PLUS_I : [[[(i8,i8); 5]; 2]; 4] = [
+    [[(0,0), (1,0), (-2,0), (-2,-1), (1,2)], [(0,0), (-1,0), (2,0), (2,-1), (-1,2)]],
+    [[(0,0), (-1,0), (2,0), (-1,2), (2,-1)], [(0,0), (-1,0), (2,0), (-1,-2), (2,1)]],
+    [[(0,0), (2,0), (-1,0), (2,1), (-1,-2)], [(0,0), (-2,0), (1,0), (-2,1), (1,-2)]],
+    [[(0,0), (1,0), (-2,0), (1,-2), (-2,1)], [(0,0), (1,0), (-2,0), (1,2), (-2,-1)]]
+];
+
+fn calc_kicks(piece : char, start : usize, end : usize) -> [(i8, i8); 5] {
+    let mut kicks : [(i8, i8); 5] = [(0,0); 5];
+    for k in 0..5{
+        let array = if piece == 'I' {&I_OFFSET_DATA} else {&OFFSET_DATA};
+        let (start_x,start_y) = array[start][k];
+        let (end_x, end_y) = array[end][k];
+
+        kicks[k] = (start_x - end_x, start_y - end_y);
+    }
+    kicks
+}
+
+fn calc_o_kick(start : usize, end : usize) -> (i8, i8) {
+    let array = O_OFFESET_DATA;
+    let (start_x,start_y) = array[start];
+    let (end_x, end_y) = array[end];
+
+    (start_x - end_x, start_y - end_y)
+}
+
+/// the guideline rotation system, `plus` adds the TETR.IO 180 and I kicks
+#[derive(Clone)]
+pub struct Srs {
+    plus : bool,
+}
+
+impl RotationSystem for Srs {
+    fn tetromino(&self, piece : &Piece, rotation : usize) -> Tetromino{
+        piece.rotations[rotation]
+    }
+
+    fn kicks(&self, piece : &Piece, start : usize, end : usize) -> Vec<(i8, i8)>{
+        if piece.name == 'O'{
+            return vec![calc_o_kick(start, end)];
+        }
+        let kicks = calc_kicks(piece.name, start, end);
+        // the first offset only moves the piece back around its true rotation center
+        let center = kicks[0];
+        let shift = |table : &[(i8, i8)]| table.iter()
+            .map(|(x, y)| (center.0 + x, center.1 + y))
+            .collect();
+
+        if (start + 2) % 4 == end{
+            return if self.plus {shift(&SRS_PLUS_180[start])} else {vec![center]};
+        }
+        if self.plus && piece.name == 'I'{
+            let direction = if (start + 1) % 4 == end {0} else {1};
+            return shift(&SRS_PLUS_I[start][direction]);
+        }
+        kicks.to_vec()
+    }
+}
+
+/// Arika rotation system (TGM): pieces rest on the bottom of their box and only
+/// kick one cell right or left
+#[derive(Clone)]
+pub struct Ars {}
+
+static ARS_SHAPES : [[Tetromino; 4]; 7] = [
+    [
+        [(-1, 0), (0, 0), (1, 0), (2, 0)],
+        [(1, 1), (1, 0), (1, -1), (1, -2)],
+        [(-1, 0), (0, 0), (1, 0), (2, 0)],
+        [(1, 1), (1, 0), (1, -1), (1, -2)]
+    ],
+    [
+        [(-1, 0), (0, 0), (1, 0), (1, -1)],
+        [(0, 1), (0, 0), (-1, -1), (0, -1)],
+        [(-1, 0), (-1, -1), (0, -1), (1, -1)],
+        [(0, 1), (1, 1), (0, 0), (0, -1)]
+    ],
+    [
+        [(-1, 0), (0, 0), (1, 0), (-1, -1)],
+        [(-1, 1), (0, 1), (0, 0), (0, -1)],
+        [(1, 0), (-1, -1), (0, -1), (1, -1)],
+        [(0, 1), (0, 0), (0, -1), (1, -1)]
+    ],
+    [
+        [(0, 0), (1, 0), (0, -1), (1, -1)],
+        [(0, 0), (1, 0), (0, -1), (1, -1)],
+        [(0, 0), (1, 0), (0, -1), (1, -1)],
+        [(0, 0), (1, 0), (0, -1), (1, -1)]
+    ],
+    [
+        [(0, 0), (1, 0), (-1, -1), (0, -1)],
+        [(-1, 1), (-1, 0), (0, 0), (0, -1)],
+        [(0, 0), (1, 0), (-1, -1), (0, -1)],
+        [(-1, 1), (-1, 0), (0, 0), (0, -1)]
+    ],
+    [
+        [(-1, 0), (0, 0), (1, 0), (0, -1)],
+        [(0, 1), (-1, 0), (0, 0), (0, -1)],
+        [(0, 0), (-1, -1), (0, -1), (1, -1)],
+        [(0, 1), (0, 0), (1, 0), (0, -1)]
+    ],
+    [
+        [(-1, 0), (0, 0), (0, -1), (1, -1)],
+        [(1, 1), (0, 0), (1, 0), (0, -1)],
+        [(-1, 0), (0, 0), (0, -1), (1, -1)],
+        [(1, 1), (0, 0), (1, 0), (0, -1)]
+    ]
+];
+
+impl RotationSystem for Ars {
+    fn tetromino(&self, piece : &Piece, rotation : usize) -> Tetromino{
+        ARS_SHAPES[piece_index(piece.name).unwrap()][rotation]
+    }
+
+    fn kicks(&self, piece : &Piece, _start : usize, _end : usize) -> Vec<(i8, i8)>{
+        if piece.name == 'I'{
+            return vec![(0,0)];
+        }
+        vec![(0,0), (1,0), (-1,0)]
+    }
+
+    // L, J and T don't kick when the first blocked cell, in reading order, is in the center column
+    fn can_kick(&self, piece : &Piece, tetromino : &Tetromino, blocked : &dyn Fn((i8, i8)) -> bool) -> bool{
+        if !['L', 'J', 'T'].contains(&piece.name){
+            return true;
+        }
+        let mut cells = tetromino.to_vec();
+        cells.sort_by_key(|&(x, y)| (-y, x));
+        match cells.into_iter().find(|&cell| blocked(cell)) {
+            Some((x, _)) => x != 0,
+            None => true,
+        }
+    }
+}
+
+/// Nintendo rotation system (NES): no kicks at all
+#[derive(Clone)]
+pub struct Nrs {}
+
+static NRS_SHAPES : [[Tetromino; 4]; 7] = [
+    [
+        [(-2, 0), (-1, 0), (0, 0), (1, 0)],
+        [(0, 1), (0, 0), (0, -1), (0, -2)],
+        [(-2, 0), (-1, 0), (0, 0), (1, 0)],
+        [(0, 1), (0, 0), (0, -1), (0, -2)]
+    ],
+    [
+        [(-1, 0), (0, 0), (1, 0), (1, -1)],
+        [(0, 1), (0, 0), (-1, -1), (0, -1)],
+        [(-1, 1), (-1, 0), (0, 0), (1, 0)],
+        [(0, 1), (1, 1), (0, 0), (0, -1)]
+    ],
+    [
+        [(-1, 0), (0, 0), (1, 0), (-1, -1)],
+        [(-1, 1), (0, 1), (0, 0), (0, -1)],
+        [(1, 1), (-1, 0), (0, 0), (1, 0)],
+        [(0, 1), (0, 0), (0, -1), (1, -1)]
+    ],
+    [
+        [(-1, 0), (0, 0), (-1, -1), (0, -1)],
+        [(-1, 0), (0, 0), (-1, -1), (0, -1)],
+        [(-1, 0), (0, 0), (-1, -1), (0, -1)],
+        [(-1, 0), (0, 0), (-1, -1), (0, -1)]
+    ],
+    [
+        [(0, 0), (1, 0), (-1, -1), (0, -1)],
+        [(0, 1), (0, 0), (1, 0), (1, -1)],
+        [(0, 0), (1, 0), (-1, -1), (0, -1)],
+        [(0, 1), (0, 0), (1, 0), (1, -1)]
+    ],
+    [
+        [(-1, 0), (0, 0), (1, 0), (0, -1)],
+        [(0, 1), (-1, 0), (0, 0), (0, -1)],
+        [(0, 1), (-1, 0), (0, 0), (1, 0)],
+        [(0, 1), (0, 0), (1, 0), (0, -1)]
+    ],
+    [
+        [(-1, 0), (0, 0), (0, -1), (1, -1)],
+        [(1, 1), (0, 0), (1, 0), (0, -1)],
+        [(-1, 0), (0, 0), (0, -1), (1, -1)],
+        [(1, 1), (0, 0), (1, 0), (0, -1)]
+    ]
+];
+
+impl RotationSystem for Nrs {
+    fn tetromino(&self, piece : &Piece, rotation : usize) -> Tetromino{
+        NRS_SHAPES[piece_index(piece.name).unwrap()][rotation]
+    }
+
+    fn kicks(&self, _piece : &Piece, _start : usize, _end : usize) -> Vec<(i8, i8)>{
+        vec![(0,0)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tetris_engine::tests::{board_game, board_game_with, put};
+    use crate::tetris_engine::{Game, Rules, SpinType};
+
+    fn rules(kind : RotationSystemKind) -> Rules{
+        let mut rules = Rules::new();
+        rules.rotation_system = kind;
+        rules
+    }
+
+    // the cells of the piece in play, as (x, rows from the bottom)
+    fn cells(game : &Game) -> Vec<(i8, i8)>{
+        let bottom = game.board.len() as i8 - 1;
+        let (x, y) = game.current_position;
+        let mut cells : Vec<(i8, i8)> = game.current_tetromino().iter().map(|(t_x, t_y)| (x + t_x, bottom - (y - t_y))).collect();
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn srs_tst_kick(){
+        // only the last kick, one left and two down, fits
+        let mut game = board_game(&["##........", "#...######", "#.########", "#..#######", "#.########"]);
+        put(&mut game, 'T', 0, [(1, 3), (2, 3), (3, 3), (2, 4)]);
+        assert!(game.rotate(1));
+        assert_eq!(cells(&game), [(1, 0), (1, 1), (1, 2), (2, 1)]);
+        let result = game.place();
+        assert_eq!((result.spin, result.cleared_rows.len()), (SpinType::FULL, 3));
+    }
+
+    #[test]
+    fn srs_i_wall_kick(){
+        let mut game = board_game(&[]);
+        put(&mut game, 'I', 1, [(0, 5), (0, 6), (0, 7), (0, 8)]);
+        assert!(game.rotate(1));
+        assert_eq!(game.current_rotation, 2);
+        assert_ne!(game.last_kick, 0);
+        assert_eq!(cells(&game), [(0, 6), (1, 6), (2, 6), (3, 6)]);
+    }
+
+    #[test]
+    fn srs_plus_180_kick(){
+        // a T flat on the floor has no room to turn over in place
+        let on_floor = [(3, 0), (4, 0), (5, 0), (4, 1)];
+        let mut game = board_game(&[]);
+        put(&mut game, 'T', 0, on_floor);
+        assert!(!game.rotate(2));
+
+        let mut game = board_game_with(rules(RotationSystemKind::SRS_PLUS), &[]);
+        put(&mut game, 'T', 0, on_floor);
+        assert!(game.rotate(2));
+        assert_eq!(cells(&game), [(3, 1), (4, 0), (4, 1), (5, 1)]);
+    }
+
+    #[test]
+    fn ars_kicks_except_on_the_center_column(){
+        // turning away from the wall kicks one cell right
+        let mut game = board_game_with(rules(RotationSystemKind::ARS), &[]);
+        put(&mut game, 'T', 3, [(0, 4), (0, 5), (0, 6), (1, 5)]);
+        assert!(game.rotate(1));
+        assert_eq!(cells(&game), [(0, 5), (1, 4), (1, 5), (2, 5)]);
+
+        // blocked above the center, it doesn't turn at all
+        let mut game = board_game_with(rules(RotationSystemKind::ARS), &["....#.....", "..........", ".........."]);
+        put(&mut game, 'T', 0, [(3, 1), (4, 1), (5, 1), (4, 0)]);
+        assert!(!game.rotate(1));
+        assert!(!game.rotate(3));
+    }
+
+    #[test]
+    fn nrs_never_kicks(){
+        let mut game = board_game_with(rules(RotationSystemKind::NRS), &[]);
+        put(&mut game, 'T', 3, [(0, 4), (0, 5), (0, 6), (1, 5)]);
+        assert!(!game.rotate(1));
+        put(&mut game, 'T', 3, [(4, 4), (4, 5), (4, 6), (5, 5)]);
+        assert!(game.rotate(1));
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::randomizer::{parse_piece_names, piece_names, restore_rng, rng_state};
+use crate::replay::{parse, read_handling, read_rules, write_handling, write_rules};
+use crate::tetris_engine::{piece_index, Game, Phase, GARBAGE, PIECES};
+use crate::user_controls::{Action, UserControl, BUFFER_LIMIT};
+
+const HEADER : &str = "tetris save 1";
+
+/// a game in progress with the handling timers, to continue it later
+pub struct SavedGame {
+    pub game : Game,
+    pub controls : UserControl,
+}
+
+impl SavedGame {
+    /// keys held right now are not saved, they are all released when the game is loaded
+    pub fn to_text(game : &Game, controls : &UserControl) -> String{
+        let mut text = String::new();
+        writeln!(text, "{}", HEADER).unwrap();
+        writeln!(text, "seed {}", game.seed).unwrap();
+        writeln!(text, "frames {}", game.frames).unwrap();
+        write_rules(&mut text, &game.rules);
+        write_handling(&mut text, &controls.handling);
+        writeln!(text, "controls_frame {}", controls.frame).unwrap();
+        writeln!(text, "das_charge {}", controls.hold).unwrap();
+        writeln!(text, "arr_frame {}", controls.arr).unwrap();
+        let buffered : Vec<&str> = controls.buffered.iter().map(|action| action.name()).collect();
+        writeln!(text, "buffered {}", if buffered.is_empty() {"-".to_string()} else {buffered.join(" ")}).unwrap();
+
+        writeln!(text, "score {}", game.score).unwrap();
+        writeln!(text, "lines_cleared {}", game.lines_cleared).unwrap();
+        writeln!(text, "lines_sent {}", game.lines_sent).unwrap();
+        writeln!(text, "combo {}", game.combo).unwrap();
+        writeln!(text, "b2b {}", game.b2b).unwrap();
+        writeln!(text, "piece {}", game.current_piece.name).unwrap();
+        writeln!(text, "rotation {}", game.current_rotation).unwrap();
+        writeln!(text, "position {} {}", game.current_position.0, game.current_position.1).unwrap();
+        writeln!(text, "hold {}", game.hold_piece.map_or('-', |piece| piece.name)).unwrap();
+        writeln!(text, "already_switched {}", game.already_switched).unwrap();
+        writeln!(text, "last_rotation {}", game.last_rotation).unwrap();
+        writeln!(text, "last_kick {}", game.last_kick).unwrap();
+        let phase = match game.phase {
+            Phase::FALLING => "falling 0".to_string(),
+            Phase::LINE_CLEAR(frames) => format!("line_clear {}", frames),
+            Phase::ARE(frames) => format!("are {}", frames),
+            Phase::TIME_UP => "time_up 0".to_string(),
+        };
+        writeln!(text, "phase {}", phase).unwrap();
+        let clearing : Vec<String> = game.clearing_rows.iter().map(|row| row.to_string()).collect();
+        writeln!(text, "clearing_rows {}", if clearing.is_empty() {"-".to_string()} else {clearing.join(" ")}).unwrap();
+        writeln!(text, "lock_timer {}", game.lock_timer).unwrap();
+        writeln!(text, "lock_resets {}", game.lock_resets).unwrap();
+        writeln!(text, "lowest_row {}", game.lowest_row).unwrap();
+        writeln!(text, "drop_points {}", game.drop_points).unwrap();
+        writeln!(text, "fall {}", game.fall).unwrap();
+        let garbage : Vec<String> = game.garbage.batches().map(|batch| format!("{}:{}", batch.lines, batch.hole)).collect();
+        writeln!(text, "garbage {}", if garbage.is_empty() {"-".to_string()} else {garbage.join(" ")}).unwrap();
+        writeln!(text, "rng {}", rng_state(&game.rng)).unwrap();
+        writeln!(text, "randomizer_state {}", game.randomizer.state()).unwrap();
+        writeln!(text, "next {}", piece_names(&game.next_pieces.iter().copied().collect::<Vec<usize>>())).unwrap();
+
+        // one digit per cell, board cell indices
+        writeln!(text, "board").unwrap();
+        for row in &game.board{
+            let row : String = row.iter().map(|cell| char::from_digit(*cell as u32, 10).unwrap()).collect();
+            writeln!(text, "{}", row).unwrap();
+        }
+        text
+    }
+
+    pub fn from_text(text : &str) -> Result<Self, String>{
+        let mut lines = text.lines();
+        if lines.next() != Some(HEADER){
+            return Err("not a save".to_string());
+        }
+        let mut fields = HashMap::new();
+        for line in lines.by_ref(){
+            if line == "board"{
+                break;
+            }
+            let (key, value) = line.split_once(' ').ok_or(format!("bad line: {}", line))?;
+            fields.insert(key, value);
+        }
+        let piece = |key : &str| -> Result<Option<usize>, String>{
+            let name = fields.get(key).ok_or(format!("missing {}", key))?;
+            if *name == "-"{
+                return Ok(None);
+            }
+            let index = name.chars().next().and_then(piece_index).filter(|_| name.len() == 1);
+            index.map(Some).ok_or(format!("bad {}: {}", key, name))
+        };
+        let list = |key : &str| -> Vec<&str>{
+            fields.get(key).map_or(Vec::new(), |value| value.split(' ').filter(|item| *item != "-").collect())
+        };
+
+        let rules = read_rules(&fields)?;
+        rules.validate()?;
+        let mut game = Game::new(rules, parse(&fields, "seed")?);
+        let mut controls = UserControl::new(read_handling(&fields)?);
+        controls.frame = parse(&fields, "controls_frame")?;
+        controls.hold = parse(&fields, "das_charge")?;
+        controls.arr = parse(&fields, "arr_frame")?;
+        if list("buffered").len() > BUFFER_LIMIT{
+            return Err("bad buffered".to_string());
+        }
+        for action in list("buffered"){
+            controls.buffered.push(Action::from_name(action).ok_or(format!("bad action: {}", action))?);
+        }
+
+        game.score = parse(&fields, "score")?;
+        game.lines_cleared = parse(&fields, "lines_cleared")?;
+        game.lines_sent = parse(&fields, "lines_sent")?;
+        game.combo = parse(&fields, "combo")?;
+        game.b2b = parse(&fields, "b2b")?;
+        // -1 is no combo or back to back, the score can't take less
+        if game.combo < -1 || game.b2b < -1{
+            return Err("bad combo or b2b".to_string());
+        }
+        game.current_piece = &PIECES[piece("piece")?.ok_or("missing piece")?];
+        game.current_rotation = parse::<usize>(&fields, "rotation")? % 4;
+        let position = list("position");
+        let [x, y] = position.as_slice() else {
+            return Err("bad position".to_string());
+        };
+        game.current_position = (
+            x.parse().map_err(|_| "bad position")?,
+            y.parse().map_err(|_| "bad position")?,
+        );
+        game.hold_piece = piece("hold")?.map(|piece| &PIECES[piece]);
+        game.already_switched = parse(&fields, "already_switched")?;
+        game.last_rotation = parse(&fields, "last_rotation")?;
+        game.last_kick = parse(&fields, "last_kick")?;
+        let phase = list("phase");
+        game.phase = match phase.as_slice() {
+            ["falling", _] => Phase::FALLING,
+            ["line_clear", frames] => Phase::LINE_CLEAR(frames.parse().map_err(|_| "bad phase")?),
+            ["are", frames] => Phase::ARE(frames.parse().map_err(|_| "bad phase")?),
+            ["time_up", _] => Phase::TIME_UP,
+            _ => return Err("bad phase".to_string()),
+        };
+        game.frames = parse(&fields, "frames")?;
+        game.lock_timer = parse(&fields, "lock_timer")?;
+        game.lock_resets = parse(&fields, "lock_resets")?;
+        game.lowest_row = parse(&fields, "lowest_row")?;
+        if !near_board(&game, (0, game.lowest_row)){
+            return Err("bad lowest_row".to_string());
+        }
+        game.drop_points = parse(&fields, "drop_points")?;
+        game.fall = parse(&fields, "fall")?;
+        for batch in list("garbage"){
+            let (lines, hole) = batch.split_once(':').ok_or(format!("bad garbage: {}", batch))?;
+            let hole = hole.parse().ok().filter(|hole| *hole < game.width).ok_or(format!("bad garbage: {}", batch))?;
+            game.garbage.push(lines.parse().map_err(|_| format!("bad garbage: {}", batch))?, hole);
+        }
+        game.rng = restore_rng(fields.get("rng").ok_or("missing rng")?)?;
+        game.randomizer.restore(fields.get("randomizer_state").ok_or("missing randomizer_state")?)?;
+        game.next_pieces = parse_piece_names(fields.get("next").ok_or("missing next")?)?.into();
+        if game.next_pieces.len() < game.get_nexts().len(){
+            return Err("bad next".to_string());
+        }
+
+        let board : Vec<Vec<u8>> = lines
+            .map(|row| row.chars().map(|cell| cell.to_digit(10).map(|cell| cell as u8).filter(|cell| *cell <= GARBAGE)).collect::<Option<Vec<u8>>>())
+            .collect::<Option<Vec<Vec<u8>>>>()
+            .ok_or("bad board")?;
+        if board.len() != game.height || board.iter().any(|row| row.len() != game.width){
+            return Err("the board doesn't match the rules".to_string());
+        }
+        game.board = board;
+        for row in list("clearing_rows"){
+            let row = row.parse().ok().filter(|row| *row < game.height).ok_or(format!("bad clearing row: {}", row))?;
+            game.clearing_rows.push(row);
+        }
+        // the piece is only on the board while it falls, it is part of the stack after that
+        if !near_board(&game, game.current_position) || (game.phase == Phase::FALLING && !game.check_tetromino(game.current_position, &game.current_tetromino())){
+            return Err("bad position".to_string());
+        }
+
+        Ok(Self{
+            game,
+            controls,
+        })
+    }
+
+    pub fn save(game : &Game, controls : &UserControl, path : &str) -> std::io::Result<()>{
+        std::fs::write(path, SavedGame::to_text(game, controls))
+    }
+
+    pub fn load(path : &str) -> Result<Self, String>{
+        let text = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        SavedGame::from_text(&text)
+    }
+}
+
+// positions a piece can have, a few cells around the board at most
+fn near_board(game : &Game, (x, y) : (i8, i8)) -> bool{
+    let margin = 4;
+    (-margin..game.width as i8 + margin).contains(&x) && (-margin..game.height as i8 + margin).contains(&y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tetris_engine::Rules;
+    use crate::user_controls::Handling;
+
+    fn saved_text() -> String{
+        let mut game = Game::new(Rules::new(), 5);
+        let mut controls = UserControl::new(Handling::new());
+        for _ in 0..3{
+            controls.action(&mut game, Action::DROP, true);
+            controls.action(&mut game, Action::DROP, false);
+            controls.update(&mut game);
+        }
+        game.receive_garbage(2, None);
+        SavedGame::to_text(&game, &controls)
+    }
+
+    fn replace_field(text : &str, key : &str, value : &str) -> String{
+        let lines : Vec<String> = text.lines()
+            .map(|line| if line.split(' ').next() == Some(key) {format!("{} {}", key, value)} else {line.to_string()})
+            .collect();
+        lines.join("\n")
+    }
+
+    #[test]
+    fn round_trip(){
+        let text = saved_text();
+        let saved = SavedGame::from_text(&text).unwrap();
+        assert_eq!(saved.game.frames(), 3);
+        assert_eq!(SavedGame::to_text(&saved.game, &saved.controls), text);
+    }
+
+    #[test]
+    fn malformed_saves_are_rejected(){
+        let text = saved_text();
+        let bad = [
+            "tetris save 0\n".to_string(),
+            text.lines().take(5).collect::<Vec<&str>>().join("\n"),
+            replace_field(&text, "width", "300"),
+            replace_field(&text, "width", "2"),
+            replace_field(&text, "height", "0"),
+            replace_field(&text, "piece", "X"),
+            replace_field(&text, "position", "120 -100"),
+            replace_field(&text, "position", "4 39"),
+            replace_field(&text, "lowest_row", "127"),
+            replace_field(&text, "phase", "waiting 3"),
+            text.replace("\ndrop_points ", "\nlanding_points "),
+            replace_field(&text, "combo", "-5"),
+            replace_field(&text, "b2b", "-2"),
+            replace_field(&text, "buffered", "drop drop drop drop drop drop drop drop drop"),
+            replace_field(&text, "garbage", "2:10"),
+            replace_field(&text, "clearing_rows", "40"),
+            replace_field(&text, "next", "IJ"),
+            text.replace("\n0000000000\n", "\n0000000009\n"),
+            text.replace("\n0000000000\n", "\n000000000\n"),
+        ];
+        for text in bad{
+            assert!(SavedGame::from_text(&text).is_err(), "{}", text);
+        }
+    }
+}
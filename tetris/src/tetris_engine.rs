@@ -0,0 +1,1413 @@
+// the engine's original style: SCREAMING_CASE enum variants, camelCase in places and explicit returns
+#![allow(non_camel_case_types, non_snake_case)]
+#![allow(clippy::needless_return, clippy::upper_case_acronyms)]
+
+use std::collections::VecDeque;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+use crate::attack::AttackTable;
+use crate::garbage::GarbageQueue;
+use crate::gravity::{GravityCurve, TWENTY_G};
+use crate::mode::Mode;
+use crate::randomizer::{Randomizer, RandomizerKind};
+use crate::rotation::{RotationSystem, RotationSystemKind};
+
+/// above the visible rows there is a 20 rows buffer where pieces spawn and
+/// where the stack can still grow
+pub const BUFFER_HEIGHT : usize = 20;
+
+/// board coordinates are i8, this leaves room for piece offsets around the board
+pub const MAX_WIDTH : usize = 120;
+pub const MAX_HEIGHT : usize = 120 - BUFFER_HEIGHT;
+pub const MIN_WIDTH : usize = 4;
+
+/// board cell index used for garbage minos
+pub const GARBAGE : u8 = 8;
+
+pub type Tetromino = [(i8,i8); 4];
+
+pub struct Piece {
+    pub rotations : [Tetromino; 4],
+    pub index : u8,
+    pub name : char, 
+}
+
+pub static PIECES : [Piece; 7]   = [
+    Piece {
+        rotations : [
+            [(-1, 0), (0, 0), (1, 0), (2, 0)],
+            [(0, 1), (0, 0), (0, -1), (0, -2)],
+            [(-2, 0), (-1, 0), (0, 0), (1, 0)],
+            [(0, 2), (0, 1), (0, 0), (0, -1)]
+        ],
+        index : 1,
+        name : 'I',
+    },
+    Piece {
+        rotations : [
+            [(-1, 1), (-1, 0), (0, 0), (1, 0)],
+            [(0, 1), (1, 1), (0, 0), (0, -1)],
+            [(-1, 0), (0, 0), (1, 0), (1, -1)],
+            [(0, 1), (0, 0), (0, -1), (-1, -1)]
+        ],
+        index : 6,
+        name : 'J',
+    },
+    Piece {
+        rotations : [
+            [(-1, 0), (0, 0), (1, 0), (1, 1)],
+            [(0, 1), (0, 0), (0, -1), (1, -1)],
+            [(-1, 0), (0, 0), (1, 0), (-1, -1)],
+            [(-1, 1), (0, 1), (0, 0), (0, -1)]
+        ],
+        index : 7,
+        name : 'L',
+    },
+    Piece {
+        rotations : [
+            [(0, 1), (1, 1), (0, 0), (1, 0)],
+            [(0, 0), (1, 0), (0, -1), (1, -1)],
+            [(-1, 0), (0, 0), (-1, -1), (0, -1)],
+            [(-1, 1), (0, 1), (-1, 0), (0, 0)]
+        ],
+        index : 2,
+        name : 'O',
+    },
+    Piece {
+        rotations : [
+            [(0, 1), (1, 1), (-1, 0), (0, 0)],
+            [(0, 1), (0, 0), (1, 0), (1, -1)],
+            [(0, 0), (1, 0), (-1, -1), (0, -1)],
+            [(-1, 1), (-1, 0), (0, 0), (0, -1)]
+        ],
+        index : 4,
+        name : 'S',
+    },
+    Piece {
+        rotations : [
+            [(0, 1), (-1, 0), (0, 0), (1, 0)],
+            [(0, 1), (0, 0), (1, 0), (0, -1)],
+            [(-1, 0), (0, 0), (1, 0), (0, -1)],
+            [(0, 1), (-1, 0), (0, 0), (0, -1)]
+        ],
+        index : 3,
+        name : 'T',
+    },
+    Piece {
+        rotations : [
+            [(-1, 1), (0, 1), (0, 0), (1, 0)],
+            [(1, 1), (0, 0), (1, 0), (0, -1)],
+            [(-1, 0), (0, 0), (0, -1), (1, -1)],
+            [(0, 1), (-1, 0), (0, 0), (-1, -1)]
+        ],
+        index : 5,
+        name : 'Z',
+    }
+];
+
+pub fn piece_index(name : char) -> Option<usize>{
+    PIECES.iter().position(|piece| piece.name == name.to_ascii_uppercase())
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SpinType {NONE, MINI, FULL}
+
+// index of the last SRS kick test, the one that turns a mini into a full t-spin
+const TST_KICK : usize = 4;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TopOut {
+    // the new piece overlaps the stack when it spawns
+    BLOCK_OUT,
+    // a piece locked completely above the visible area
+    LOCK_OUT,
+    // garbage pushed the stack out of the matrix
+    GARBAGE_OUT,
+}
+
+/// everything that happened when a piece was locked on the board
+#[derive(Clone, Debug)]
+pub struct LockResult {
+    pub cleared_rows : Vec<usize>,
+    pub spin : SpinType,
+    pub combo : i32,
+    pub b2b : i32,
+    pub perfect_clear : bool,
+    /// points for the clear and for dropping the piece
+    pub score : u32,
+    /// lines generated by the clear, and what is left of them after cancelling incoming garbage
+    pub attack : u32,
+    pub sent : u32,
+    pub top_out : Option<TopOut>,
+}
+
+impl LockResult {
+    fn top_out(reason : TopOut) -> Self{
+        Self{
+            cleared_rows : Vec::new(),
+            spin : SpinType::NONE,
+            combo : -1,
+            b2b : -1,
+            perfect_clear : false,
+            score : 0,
+            attack : 0,
+            sent : 0,
+            top_out : Some(reason),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Phase {
+    FALLING,
+    // frames left before the full rows are removed
+    LINE_CLEAR(u32),
+    // frames left before the next piece spawns
+    ARE(u32),
+    // a timed game is over, the last piece has locked and no other spawns
+    TIME_UP,
+}
+
+/// inputs held while a new piece spawns, for initial rotation and initial hold
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct SpawnInput {
+    /// quarter turns clockwise, 0 for none
+    pub rotation : usize,
+    pub hold : bool,
+}
+
+/// soft drop speed as a multiple of gravity
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SoftDropFactor {
+    FACTOR(u32),
+    // the piece goes straight to the floor
+    INFINITE,
+}
+
+impl SoftDropFactor {
+    pub fn from_name(name : &str) -> Option<Self>{
+        return match name {
+            "inf" => Some(SoftDropFactor::INFINITE),
+            _ => name.parse().ok().filter(|factor| *factor > 0).map(SoftDropFactor::FACTOR),
+        };
+    }
+
+    pub fn name(&self) -> String{
+        return match self {
+            SoftDropFactor::FACTOR(factor) => factor.to_string(),
+            SoftDropFactor::INFINITE => "inf".to_string(),
+        };
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LockReset {
+    // moves and rotations reset the lock delay, up to a limit for each new lowest row
+    MOVE_RESET,
+    // moves and rotations always reset the lock delay
+    INFINITE,
+    // only moving down resets the lock delay
+    STEP_RESET,
+}
+
+impl LockReset {
+    pub fn from_name(name : &str) -> Option<Self>{
+        return match name {
+            "move" => Some(LockReset::MOVE_RESET),
+            "infinite" => Some(LockReset::INFINITE),
+            "step" => Some(LockReset::STEP_RESET),
+            _ => None,
+        };
+    }
+
+    pub fn name(&self) -> &'static str{
+        return match self {
+            LockReset::MOVE_RESET => "move",
+            LockReset::INFINITE => "infinite",
+            LockReset::STEP_RESET => "step",
+        };
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Rules {
+    /// size of the visible part of the board
+    pub width : usize,
+    pub height : usize,
+    /// chance for each garbage line to have its hole in a different column
+    pub garbage_messiness : f32,
+    /// frames a piece can rest on the stack before locking
+    pub lock_delay : u32,
+    pub lock_reset : LockReset,
+    pub lock_reset_limit : u32,
+    /// frames between a lock and the next spawn, without and with a line clear
+    pub are : u32,
+    pub line_are : u32,
+    /// frames the full rows stay on the board before being removed
+    pub line_clear_delay : u32,
+    /// max garbage lines that can rise after a single piece
+    pub garbage_cap : u32,
+    pub attack_table : AttackTable,
+    pub randomizer : RandomizerKind,
+    pub rotation_system : RotationSystemKind,
+    pub gravity : GravityCurve,
+    /// placements that can be undone in practice, 0 for none
+    pub undo_limit : usize,
+    pub mode : Mode,
+}
+
+impl Default for Rules {
+    fn default() -> Self{
+        return Rules::new();
+    }
+}
+
+impl Rules {
+    /// the board size fits the engine's coordinates and a fixed sequence has valid pieces
+    pub fn validate(&self) -> Result<(), String>{
+        if !(MIN_WIDTH..=MAX_WIDTH).contains(&self.width){
+            return Err(format!("the board must be {} to {} cells wide", MIN_WIDTH, MAX_WIDTH));
+        }
+        if !(1..=MAX_HEIGHT).contains(&self.height){
+            return Err(format!("the board must be 1 to {} cells high", MAX_HEIGHT));
+        }
+        if let RandomizerKind::FIXED(sequence) = &self.randomizer{
+            if sequence.is_empty(){
+                return Err("a fixed sequence needs at least one piece".to_string());
+            }
+            if sequence.iter().any(|piece| *piece >= PIECES.len()){
+                return Err("a fixed sequence can only have the 7 pieces".to_string());
+            }
+        }
+        return Ok(());
+    }
+
+    /// the default rules in any mode, the only ones records are kept for
+    pub fn is_standard(&self) -> bool{
+        let mut standard = Rules::new();
+        standard.mode = self.mode;
+        return *self == standard;
+    }
+
+    pub fn new() -> Self{
+        Self{
+            width : 10,
+            height : 20,
+            garbage_messiness : 0.0,
+            lock_delay : 30,
+            lock_reset : LockReset::MOVE_RESET,
+            lock_reset_limit : 15,
+            are : 0,
+            line_are : 0,
+            line_clear_delay : 0,
+            garbage_cap : 8,
+            attack_table : AttackTable::GUIDELINE,
+            randomizer : RandomizerKind::BAG_7,
+            rotation_system : RotationSystemKind::SRS,
+            gravity : GravityCurve::GUIDELINE,
+            undo_limit : 0,
+            mode : Mode::MARATHON,
+        }
+    }
+}
+
+const NEXT_PIECES : usize = 5;
+
+#[derive(Clone)]
+pub struct Game{
+    /// rows from the top of the buffer down to the floor
+    pub board : Vec<Vec<u8>>,
+    pub(crate) width : usize,
+    pub(crate) height : usize,
+    pub current_piece : &'static Piece,
+
+    pub current_rotation : usize,
+    pub current_position : (i8, i8),
+
+    pub(crate) top_out : Option<TopOut>,
+
+    pub score : u32,
+    pub lines_cleared : u32,
+    pub lines_sent : u32,
+
+    pub hold_piece : Option<&'static Piece>,
+    pub already_switched : bool,
+
+    pub(crate) last_rotation : bool,
+    pub(crate) last_kick : usize,
+
+    pub(crate) phase : Phase,
+    spawn_input : SpawnInput,
+    pub(crate) clearing_rows : Vec<usize>,
+    pub(crate) lock_timer : u32,
+    pub(crate) frames : u32,
+    // rows the piece has to fall, carried between frames
+    pub(crate) fall : f32,
+    soft_drop : Option<SoftDropFactor>,
+    pub(crate) lock_resets : u32,
+    pub(crate) lowest_row : i8,
+    // soft and hard drop points of the piece in play, reported when it locks
+    pub(crate) drop_points : u32,
+
+    /// -1 when there is no combo / back-to-back chain going on
+    pub combo : i32,
+    pub b2b : i32,
+
+    pub garbage : GarbageQueue,
+
+    pub seed : u64,
+    pub(crate) rng : ChaCha12Rng,
+    pub(crate) rules : Rules,
+    pub(crate) randomizer : Box<dyn Randomizer>,
+    pub(crate) rotation_system : Box<dyn RotationSystem>,
+    pub(crate) next_pieces : VecDeque<usize>,
+}
+
+impl Game{
+
+    /// the same seed always gives the same piece sequence and garbage holes,
+    /// panics when `Rules::validate` fails
+    pub fn new(rules : Rules, seed : u64) -> Self{
+        if let Err(error) = rules.validate(){
+            panic!("{}", error);
+        }
+        let mut randomizer = rules.randomizer.build(seed).unwrap_or_else(|error| panic!("{}", error));
+        let piece_1 = &PIECES[randomizer.next()];
+        let next_pieces = (0..NEXT_PIECES).map(|_| randomizer.next()).collect();
+        let rotation_system = rules.rotation_system.build();
+        let (width, height) = (rules.width, rules.height + BUFFER_HEIGHT);
+
+        let mut game = Self{
+            board : vec![vec![0; width]; height],
+            width,
+            height,
+            current_piece : piece_1,
+            current_rotation : 0,
+            current_position : (0, 0), 
+            top_out : None,
+            score : 0,
+            lines_cleared : 0,
+            lines_sent : 0,
+            hold_piece : None,
+            already_switched : false,
+            last_rotation : false,
+            last_kick : 0,
+            phase : Phase::FALLING,
+            spawn_input : SpawnInput::default(),
+            clearing_rows : Vec::new(),
+            lock_timer : 0,
+            frames : 0,
+            fall : 0.0,
+            soft_drop : None,
+            lock_resets : 0,
+            lowest_row : 0,
+            drop_points : 0,
+            combo : -1,
+            b2b : -1,
+            garbage : GarbageQueue::new(),
+            seed,
+            rng : ChaCha12Rng::seed_from_u64(!seed),
+            rules,
+            randomizer,
+            rotation_system,
+            next_pieces,
+        };
+        game.summon_piece(piece_1);
+        return game;
+    }
+
+    pub fn rules(&self) -> &Rules{
+        &self.rules
+    }
+
+    pub fn width(&self) -> usize{
+        self.width
+    }
+
+    pub fn visible_height(&self) -> usize{
+        self.height - BUFFER_HEIGHT
+    }
+
+    // just above the skyline, centered
+    fn spawn_position(&self) -> (i8, i8){
+        ((self.width as i8 - 1) / 2, BUFFER_HEIGHT as i8 - 1)
+    }
+
+    pub fn top_out(&self) -> Option<TopOut>{
+        self.top_out
+    }
+
+    pub fn current_tetromino(&self) -> Tetromino{
+        self.rotation_system.tetromino(self.current_piece, self.current_rotation)
+    }
+
+    /// the spawn shape of a piece, for hold and next previews
+    pub fn piece_tetromino(&self, piece : &Piece) -> Tetromino{
+        self.rotation_system.tetromino(piece, 0)
+    }
+
+    pub fn check_tetromino(&self, position : (i8, i8), tetromino : &Tetromino) -> bool{
+        //println!("---");
+        for (t_x,t_y) in tetromino{
+            let (x, y) = (position.0 + t_x, position.1 - t_y);
+            //println!("{} {}", x, y);
+            if x < 0 || x>=self.width as i8 || y < 0 || y>=self.height as i8{return false;}
+            if self.board[y as usize][x as usize] != 0 {return false;}
+ 
+        }
+        //println!("---");
+        return true;
+    }
+
+    pub fn hold_piece(&mut self) -> bool{
+        // holding spawns a piece, there are no new ones once the time is out
+        if self.already_switched || !self.piece_active() || self.out_of_time(){
+            return false;
+        }
+        self.already_switched = true;
+        match self.hold_piece{
+            Some(piece) => {
+                let hold = self.current_piece;
+                self.summon_piece(piece);
+                self.hold_piece = Some(hold);
+                
+            },
+            None => {
+                self.hold_piece = Some(self.current_piece);
+                let x = self.get_next();
+                self.summon_piece(x);
+            },
+        }   
+        return true;
+    }
+
+    pub fn get_next(&mut self) -> &'static Piece{
+        self.next_pieces.push_back(self.randomizer.next());
+        return &PIECES[self.next_pieces.pop_front().unwrap()];
+    }
+
+    pub fn get_nexts(&self) -> [&Piece; NEXT_PIECES]{
+        let mut pieces = [&PIECES[0]; NEXT_PIECES];
+        for (i, &idx) in self.next_pieces.iter().take(NEXT_PIECES).enumerate(){
+            pieces[i] = &PIECES[idx];
+        }
+        return pieces;
+    }
+
+    /// starts over from the given pieces, as indices in PIECES, the randomizer fills the
+    /// rest of the next queue; used to set up a position like a fumen, so the score,
+    /// the counters, the garbage and the clock start over too
+    pub fn set_pieces(&mut self, current : usize, hold : Option<usize>, next : &[usize]){
+        self.score = 0;
+        self.lines_cleared = 0;
+        self.lines_sent = 0;
+        self.combo = -1;
+        self.b2b = -1;
+        self.garbage = GarbageQueue::new();
+        self.frames = 0;
+        self.next_pieces = next.iter().copied().collect();
+        while self.next_pieces.len() < NEXT_PIECES{
+            self.next_pieces.push_back(self.randomizer.next());
+        }
+        self.hold_piece = hold.map(|piece| &PIECES[piece]);
+        self.already_switched = false;
+        self.phase = Phase::FALLING;
+        self.clearing_rows.clear();
+        self.spawn_input = SpawnInput::default();
+        self.top_out = None;
+        self.summon_piece(&PIECES[current]);
+    }
+
+    pub fn move_piece(&mut self, dir : i8) -> bool{
+        if !self.piece_active(){
+            return false;
+        }
+        let tetromino = &self.current_tetromino();
+        let (x, y) = (self.current_position.0 + dir, self.current_position.1);
+        if self.check_tetromino((x,y), tetromino){ 
+            let on_ground = self.on_ground();
+            self.current_position.0 += dir;
+            self.last_rotation = false;
+            self.reset_lock_delay(on_ground);
+            return true
+        }
+        return false
+    }
+
+    fn on_ground(&self) -> bool{
+        let (x, y) = self.current_position;
+        !self.check_tetromino((x, y + 1), &self.current_tetromino())
+    }
+
+    // called after a successful move or rotation
+    fn reset_lock_delay(&mut self, on_ground : bool){
+        match self.rules.lock_reset {
+            LockReset::MOVE_RESET => {
+                if !on_ground{
+                    self.lock_timer = 0;
+                }else if self.lock_resets < self.rules.lock_reset_limit{
+                    self.lock_timer = 0;
+                    self.lock_resets += 1;
+                }
+            },
+            LockReset::INFINITE => self.lock_timer = 0,
+            LockReset::STEP_RESET => (),
+        }
+    }
+
+    /// advances the game by one frame: lock delay, line clear delay and ARE
+    pub fn update(&mut self) -> Option<LockResult>{
+        if self.top_out.is_some() || self.time_up(){
+            return None;
+        }
+        // the piece in play when the time runs out keeps falling until it locks
+        self.frames += 1;
+        match self.phase {
+            Phase::FALLING => {
+                self.apply_gravity();
+                if !self.on_ground(){
+                    return None;
+                }
+                self.lock_timer += 1;
+                if self.lock_timer >= self.rules.lock_delay{
+                    return Some(self.place());
+                }
+            },
+            Phase::LINE_CLEAR(frames) => {
+                if frames > 1{
+                    self.phase = Phase::LINE_CLEAR(frames - 1);
+                }else{
+                    let rows = std::mem::take(&mut self.clearing_rows);
+                    self.collapse_rows(&rows);
+                    self.start_are(self.rules.line_are);
+                }
+            },
+            Phase::ARE(frames) => {
+                if frames > 1{
+                    self.phase = Phase::ARE(frames - 1);
+                }else{
+                    self.spawn_next();
+                }
+            },
+            Phase::TIME_UP => (),
+        }
+        return None;
+    }
+
+    /// rows per frame at the current level
+    pub fn gravity(&self) -> f32{
+        return self.rules.gravity.gravity_at_level(self.get_level(), self.lines_cleared);
+    }
+
+    /// set before every frame, soft drops while it is Some
+    pub fn set_soft_drop(&mut self, soft_drop : Option<SoftDropFactor>){
+        self.soft_drop = soft_drop;
+    }
+
+    fn apply_gravity(&mut self){
+        let gravity = self.gravity();
+        self.fall += match self.soft_drop {
+            Some(SoftDropFactor::INFINITE) => {
+                self.sonic_drop();
+                self.fall = 0.0;
+                return;
+            },
+            Some(SoftDropFactor::FACTOR(sdf)) => (gravity * sdf as f32).min(TWENTY_G),
+            None => gravity,
+        };
+        while self.fall >= 1.0{
+            self.fall -= 1.0;
+            let moved = if self.soft_drop.is_some() {self.soft_drop()} else {self.drop()};
+            if !moved{
+                self.fall = 0.0;
+            }
+        }
+    }
+
+    fn start_are(&mut self, frames : u32){
+        if frames == 0 || self.out_of_time(){
+            self.spawn_next();
+        }else{
+            self.phase = Phase::ARE(frames);
+        }
+    }
+
+    fn spawn_next(&mut self){
+        if self.out_of_time(){
+            self.phase = Phase::TIME_UP;
+            return;
+        }
+        self.phase = Phase::FALLING;
+        self.already_switched = false;
+        let x = self.get_next();
+        if !self.spawn_input.hold{
+            self.summon_piece(x);
+            return;
+        }
+        // initial hold: the new piece goes straight into hold
+        self.already_switched = true;
+        match self.hold_piece.replace(x){
+            Some(piece) => self.summon_piece(piece),
+            None => {
+                let x = self.get_next();
+                self.summon_piece(x);
+            },
+        }
+    }
+
+    /// set before every frame, what is held gets applied when the next piece spawns
+    pub fn set_spawn_input(&mut self, input : SpawnInput){
+        self.spawn_input = input;
+    }
+
+    /// false during line clear delay and ARE, when there is no piece to control,
+    /// and once the game is over
+    pub fn piece_active(&self) -> bool{
+        self.phase == Phase::FALLING && self.top_out.is_none()
+    }
+
+    /// frames played, one for every update
+    pub fn frames(&self) -> u32{
+        self.frames
+    }
+
+    /// frames left in a timed mode
+    pub fn time_left(&self) -> Option<u32>{
+        self.rules.mode.time_limit().map(|limit| limit.saturating_sub(self.frames))
+    }
+
+    /// the game is over in a timed mode: the time ran out and the piece in play then has locked
+    pub fn time_up(&self) -> bool{
+        self.phase == Phase::TIME_UP
+    }
+
+    // no piece spawns anymore, the one in play can still be placed
+    fn out_of_time(&self) -> bool{
+        self.time_left() == Some(0)
+    }
+
+    /// full rows waiting for the line clear delay to end
+    pub fn clearing_rows(&self) -> &[usize]{
+        &self.clearing_rows
+    }
+
+    fn summon_piece(&mut self, piece : &'static Piece){
+        self.current_piece = piece;
+        self.current_rotation = 0;
+        self.current_position = self.spawn_position();
+        self.last_rotation = false;
+        self.lock_timer = 0;
+        self.lock_resets = 0;
+        self.drop_points = 0;
+        self.lowest_row = self.current_position.1;
+        if !self.check_tetromino(self.current_position, &self.current_tetromino()){
+            self.top_out = Some(TopOut::BLOCK_OUT);
+            return;
+        }
+        // initial rotation, the piece spawns unrotated if the rotated one doesn't fit
+        let rotation = self.spawn_input.rotation % 4;
+        if rotation != 0{
+            let tetromino = self.rotation_system.tetromino(piece, rotation);
+            if self.check_tetromino(self.current_position, &tetromino){
+                self.current_rotation = rotation;
+            }
+        }
+        // the piece moves down into the visible area right away if it can
+        self.drop();
+        self.fall = 0.0;
+        // at 20G the piece lands as soon as it spawns
+        if self.gravity() >= TWENTY_G{
+            while self.drop(){
+
+            }
+        }
+    }
+
+    /// 2 points for every cell hard dropped
+    pub fn hard_drop(&mut self) -> Option<LockResult>{
+        if !self.piece_active(){
+            return None;
+        }
+        while self.drop(){
+            self.score += 2;
+            self.drop_points += 2;
+        }
+        return Some(self.place());
+    }
+
+    /// one row down, 1 point for every cell soft dropped
+    pub fn soft_drop(&mut self) -> bool{
+        if self.drop(){
+            self.score += 1;
+            self.drop_points += 1;
+            return true;
+        }
+        return false;
+    }
+
+    /// infinite soft drop, straight to the floor without locking
+    pub fn sonic_drop(&mut self){
+        while self.soft_drop(){
+
+        }
+    }
+
+    pub fn drop(&mut self) -> bool{
+        if !self.piece_active(){
+            return false;
+        }
+        let (x, y) = (self.current_position.0, self.current_position.1 + 1);
+        if self.check_tetromino((x, y), &self.current_tetromino()){
+            self.current_position.1 += 1;
+            self.last_rotation = false;
+            self.lock_timer = 0;
+            if y > self.lowest_row{
+                self.lowest_row = y;
+                self.lock_resets = 0;
+            }
+            return true;
+        }
+        return false;
+    }
+
+    pub fn hard_move(&mut self, dir : i8){
+        while self.move_piece(dir){
+            
+        }
+    }
+
+    pub fn rotate(&mut self, offset : i32) -> bool{
+        if !self.piece_active(){
+            return false;
+        }
+        let new_rotation = (self.current_rotation + offset as usize) % 4;
+        let tetromino = self.rotation_system.tetromino(self.current_piece, new_rotation);
+        let kicks = self.rotation_system.kicks(
+            self.current_piece, 
+            self.current_rotation, 
+            new_rotation
+        );
+        let (pos_x, pos_y) = self.current_position;
+
+        let mut last_kick = (0,0);
+        let mut found = false;
+        for (i, kick) in kicks.iter().enumerate(){
+            if i == 1 && !self.rotation_system.can_kick(
+                self.current_piece,
+                &tetromino,
+                &|(t_x, t_y)| self.occupied((pos_x + t_x, pos_y - t_y))
+            ){
+                break
+            }
+            let (x, y) = (pos_x + kick.0, pos_y - kick.1);
+            if self.check_tetromino((x,y), &tetromino){
+                last_kick = *kick;
+                // 180 kicks never upgrade a mini t-spin
+                self.last_kick = if offset == 2 {0} else {i};
+                found = true;
+                break
+            }
+        }
+        if found {
+            let on_ground = self.on_ground();
+            self.current_position.0 += last_kick.0;
+            self.current_position.1 -= last_kick.1;
+            self.current_rotation = new_rotation;
+            self.last_rotation = true;
+            self.reset_lock_delay(on_ground);
+        }
+        return found;
+    }
+
+    fn occupied(&self, (x, y) : (i8, i8)) -> bool{
+        if x < 0 || x >= self.width as i8 || y < 0 || y >= self.height as i8 {return true;}
+        self.board[y as usize][x as usize] != 0
+    }
+
+    // 3-corner rule, must be called before the piece is written on the board
+    fn detect_spin(&self) -> SpinType{
+        if self.current_piece.name != 'T' || !self.last_rotation{
+            return SpinType::NONE;
+        }
+        // the center is the mino touching the other three, the stem the one with nothing opposite to it
+        let tetromino = self.current_tetromino();
+        let center = *tetromino.iter()
+            .find(|&&(x, y)| tetromino.iter().filter(|&&(o_x, o_y)| (o_x - x).abs() + (o_y - y).abs() == 1).count() == 3)
+            .unwrap();
+        let stem = *tetromino.iter()
+            .find(|&&(x, y)| (x, y) != center && !tetromino.contains(&(2 * center.0 - x, 2 * center.1 - y)))
+            .unwrap();
+        let (d_x, d_y) = (stem.0 - center.0, stem.1 - center.1);
+
+        let (x, y) = self.current_position;
+        let corner = |forward : i8, side : i8| self.occupied((
+            x + center.0 + forward * d_x + side * d_y,
+            y - (center.1 + forward * d_y + side * d_x)
+        ));
+        let front = [corner(1, 1), corner(1, -1)];
+        let back = [corner(-1, 1), corner(-1, -1)];
+
+        if front.iter().chain(back.iter()).filter(|&&c| c).count() < 3{
+            return SpinType::NONE;
+        }
+        if (front[0] && front[1]) || self.last_kick == TST_KICK{
+            return SpinType::FULL;
+        }
+        SpinType::MINI
+    }
+
+    // indices of the full rows, from the bottom up
+    fn full_rows(&self) -> Vec<usize>{
+        (0..self.height).rev()
+            .filter(|&y| self.board[y].iter().all(|&cell| cell != 0))
+            .collect()
+    }
+
+    // removes the given rows, from the bottom up, and shifts down what is above them
+    fn collapse_rows(&mut self, rows : &[usize]){
+        let mut line: usize = self.height;
+
+        for y in (0..self.height).rev(){
+            if rows.contains(&y) {
+                continue
+            }
+            self.board[line - 1] = self.board[y].clone();
+            line -= 1;
+        }
+        for y in (0..line).rev(){
+            self.board[y] = vec![0; self.width];
+        }
+    }
+
+    pub fn get_level(&self) -> u32{
+        return self.rules.mode.level(self.lines_cleared);
+    }
+
+    fn update_score(&mut self, lines : u32, spin : SpinType, perfect_clear : bool) -> u32{
+        let l = self.get_level();
+        let score =  match (spin, lines) {
+            (SpinType::MINI, 0) => 100,
+            (SpinType::MINI, 1) => 200,
+            (SpinType::MINI, _) => 400,
+            (SpinType::FULL, 0) => 400,
+            (SpinType::FULL, 1) => 800,
+            (SpinType::FULL, 2) => 1200,
+            (SpinType::FULL, _) => 1600,
+            (SpinType::NONE, 1) => 100,
+            (SpinType::NONE, 2) => 300,
+            (SpinType::NONE, 3) => 500,
+            (SpinType::NONE, 4) => 800,
+            _ => 0,
+        };
+
+        if lines == 0{
+            self.combo = -1;
+            self.score += score * (l+1);
+            return score * (l+1);
+        }
+
+        self.combo += 1;
+        let difficult = lines == 4 || spin != SpinType::NONE;
+        let back_to_back = difficult && self.b2b >= 0;
+        let score = if back_to_back {score * 3 / 2} else {score};
+        self.b2b = if difficult {self.b2b + 1} else {-1};
+
+        let perfect_clear_bonus = if !perfect_clear {0} else {
+            match lines {
+                1 => 800,
+                2 => 1200,
+                3 => 1800,
+                _ if back_to_back => 3200,
+                _ => 2000,
+            }
+        };
+
+        let score = (score + perfect_clear_bonus + 50 * self.combo as u32) * (l+1);
+        self.score += score;
+        return score;
+    }
+
+    // true if nothing but the given rows is left on the board
+    fn is_board_empty(&self, cleared_rows : &[usize]) -> bool{
+        self.board.iter()
+            .enumerate()
+            .all(|(y, row)| cleared_rows.contains(&y) || row.iter().all(|&cell| cell == 0))
+    }
+
+    /// queues garbage lines, a random hole column is picked when none is given
+    pub fn receive_garbage(&mut self, lines : u32, hole : Option<usize>){
+        let hole = hole.unwrap_or_else(|| self.rng.gen_range(0..self.width));
+        self.garbage.push(lines, hole.min(self.width - 1));
+    }
+
+    // raises the pending garbage from the bottom, returns false if the stack is pushed out
+    fn insert_garbage(&mut self) -> bool{
+        let messiness = self.rules.garbage_messiness.clamp(0.0, 1.0) as f64;
+        for batch in self.garbage.take(self.rules.garbage_cap){
+            let mut hole = batch.hole;
+            for _ in 0..batch.lines{
+                if self.board[0].iter().any(|&cell| cell != 0){
+                    return false;
+                }
+                let mut row = vec![GARBAGE; self.width];
+                row[hole] = 0;
+                self.board.remove(0);
+                self.board.push(row);
+
+                if self.rng.gen_bool(messiness){
+                    hole = (hole + self.rng.gen_range(1..self.width)) % self.width;
+                }
+            }
+        }
+        return true;
+    }
+
+    pub fn place(&mut self) -> LockResult{
+        let spin = self.detect_spin();
+        let mut visible = false;
+        for (t_x,t_y) in self.current_tetromino(){
+            let (x, y) = (self.current_position.0 + t_x, self.current_position.1 - t_y);
+            visible |= y as usize >= BUFFER_HEIGHT;
+
+            self.board[y as usize][x as usize] = self.current_piece.index
+        }
+        if !visible{
+            self.top_out = Some(TopOut::LOCK_OUT);
+            return LockResult::top_out(TopOut::LOCK_OUT);
+        }
+
+        let cleared_rows = self.full_rows();
+        let lines = cleared_rows.len() as u32;
+        let perfect_clear = lines > 0 && self.is_board_empty(&cleared_rows);
+        let score = self.update_score(lines, spin, perfect_clear) + std::mem::take(&mut self.drop_points);
+        self.lines_cleared += lines;
+
+        let attack = self.rules.attack_table.attack(lines, spin, self.combo, self.b2b, perfect_clear);
+        self.lines_sent += attack;
+
+        let mut sent = 0;
+        if lines > 0{
+            sent = self.garbage.cancel(attack);
+        }else if !self.insert_garbage(){
+            self.top_out = Some(TopOut::GARBAGE_OUT);
+        }
+
+        if self.top_out.is_none(){
+            if lines > 0 && self.rules.line_clear_delay > 0{
+                // the full rows stay on the board until the delay is over
+                self.clearing_rows = cleared_rows.clone();
+                self.phase = Phase::LINE_CLEAR(self.rules.line_clear_delay);
+            }else{
+                self.collapse_rows(&cleared_rows);
+                self.start_are(if lines > 0 {self.rules.line_are} else {self.rules.are});
+            }
+        }
+
+        LockResult {
+            cleared_rows,
+            spin,
+            combo : self.combo,
+            b2b : self.b2b,
+            perfect_clear,
+            score,
+            attack,
+            sent,
+            top_out : self.top_out,
+        }
+    }
+
+    pub fn get_ghost(&self) -> (i8, i8){
+        let (x, mut y) = (self.current_position.0, self.current_position.1 + 1);
+        loop {
+            if self.check_tetromino((x, y), &self.current_tetromino()){
+                y += 1;
+                continue;
+            }
+            break;
+        }
+        return (x, y-1);
+    }   
+    
+}
+    
+
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::mode::BLITZ_FRAMES;
+
+    #[test]
+    fn board_size_is_bounded(){
+        let mut rules = Rules::new();
+        rules.height = MAX_HEIGHT + 1;
+        assert!(rules.validate().is_err());
+        rules.height = 20;
+        rules.width = MAX_WIDTH + 1;
+        assert!(rules.validate().is_err());
+        rules.width = MIN_WIDTH - 1;
+        assert!(rules.validate().is_err());
+        rules.width = 10;
+        rules.randomizer = RandomizerKind::FIXED(vec![0, 9]);
+        assert!(rules.validate().is_err());
+        rules.randomizer = RandomizerKind::FIXED(vec![0, 6]);
+        assert!(rules.validate().is_ok());
+    }
+
+    #[test]
+    fn largest_board_plays(){
+        let mut rules = Rules::new();
+        rules.width = MAX_WIDTH;
+        rules.height = MAX_HEIGHT;
+        let mut game = Game::new(rules, 1);
+        assert!(game.top_out().is_none());
+        let result = game.hard_drop().unwrap();
+        assert!(result.top_out.is_none());
+        assert!(game.board[game.board.len() - 1].iter().any(|cell| *cell != 0));
+    }
+
+    #[test]
+    fn drop_points_are_in_the_lock_result(){
+        let mut game = Game::new(Rules::new(), 1);
+        game.soft_drop();
+        game.soft_drop();
+        let rows = (game.get_ghost().1 - game.current_position.1) as u32;
+        let result = game.hard_drop().unwrap();
+        assert!(result.cleared_rows.is_empty());
+        assert_eq!(result.score, 2 + 2 * rows);
+        assert_eq!(result.score, game.score);
+    }
+
+    // a game with these rows at the bottom of the board, '#' for a filled cell
+    pub(crate) fn board_game(rows : &[&str]) -> Game{
+        return board_game_with(Rules::new(), rows);
+    }
+
+    pub(crate) fn board_game_with(rules : Rules, rows : &[&str]) -> Game{
+        let mut game = Game::new(rules, 1);
+        let top = game.board.len() - rows.len();
+        for (y, row) in rows.iter().enumerate(){
+            for (x, cell) in row.chars().enumerate(){
+                game.board[top + y][x] = if cell == '#' {GARBAGE} else {0};
+            }
+        }
+        return game;
+    }
+
+    // the piece in play becomes `name`, on the given cells as (x, rows from the bottom)
+    pub(crate) fn put(game : &mut Game, name : char, rotation : usize, cells : [(i8, i8); 4]){
+        game.current_piece = &PIECES[piece_index(name).unwrap()];
+        game.current_rotation = rotation;
+        game.last_rotation = false;
+        game.last_kick = 0;
+        let bottom = game.board.len() as i8 - 1;
+        let mut wanted : Vec<(i8, i8)> = cells.iter().map(|(x, row)| (*x, bottom - row)).collect();
+        wanted.sort();
+        let tetromino = game.current_tetromino();
+        for x in -3..game.width as i8 + 3{
+            for y in 0..bottom + 3{
+                let mut placed : Vec<(i8, i8)> = tetromino.iter().map(|(t_x, t_y)| (x + t_x, y - t_y)).collect();
+                placed.sort();
+                if placed == wanted{
+                    game.current_position = (x, y);
+                    return;
+                }
+            }
+        }
+        panic!("{} in rotation {} can't be on {:?}", name, rotation, cells);
+    }
+
+    const TETRIS_WELL : &str = "#########.";
+    const I_IN_WELL : [(i8, i8); 4] = [(9, 0), (9, 1), (9, 2), (9, 3)];
+
+    #[test]
+    fn guideline_line_clear_scores(){
+        let mut game = board_game(&["#.........", "....######"]);
+        put(&mut game, 'I', 0, [(0, 0), (1, 0), (2, 0), (3, 0)]);
+        assert_eq!(game.place().score, 100);
+
+        let mut game = board_game(&["#.........", "##..######", "##..######"]);
+        put(&mut game, 'O', 0, [(2, 0), (3, 0), (2, 1), (3, 1)]);
+        assert_eq!(game.place().score, 300);
+
+        let mut game = board_game(&["#.........", TETRIS_WELL, TETRIS_WELL, TETRIS_WELL, TETRIS_WELL]);
+        put(&mut game, 'I', 1, I_IN_WELL);
+        assert_eq!(game.place().score, 800);
+        assert_eq!(game.score, 800);
+    }
+
+    #[test]
+    fn t_spin_double_scores(){
+        let mut game = board_game(&["###.......", "##...#####", "###.######"]);
+        put(&mut game, 'T', 2, [(2, 1), (3, 1), (4, 1), (3, 0)]);
+        game.last_rotation = true;
+        let result = game.place();
+        assert_eq!(result.spin, SpinType::FULL);
+        assert_eq!(result.cleared_rows.len(), 2);
+        assert_eq!(result.score, 1200);
+    }
+
+    const T_SPIN_SINGLE : [&str; 3] = ["###.......", "##...###..", "###.######"];
+    const T_IN_SLOT : [(i8, i8); 4] = [(2, 1), (3, 1), (4, 1), (3, 0)];
+    // the T points up on the floor, only one of the corners in front of it is filled
+    const T_SPIN_MINI : [&str; 2] = ["#.........", ".........."];
+    const T_ON_FLOOR : [(i8, i8); 4] = [(0, 0), (1, 0), (2, 0), (1, 1)];
+
+    #[test]
+    fn three_corner_t_spins(){
+        let mut game = board_game(&T_SPIN_SINGLE);
+        put(&mut game, 'T', 2, T_IN_SLOT);
+        game.last_rotation = true;
+        let result = game.place();
+        assert_eq!((result.spin, result.cleared_rows.len()), (SpinType::FULL, 1));
+        assert_eq!(result.score, 800);
+
+        let mut game = board_game(&T_SPIN_MINI);
+        put(&mut game, 'T', 0, T_ON_FLOOR);
+        game.last_rotation = true;
+        let result = game.place();
+        assert_eq!((result.spin, result.score), (SpinType::MINI, 100));
+
+        // only two corners filled
+        let mut game = board_game(&["..........", ".........."]);
+        put(&mut game, 'T', 0, T_ON_FLOOR);
+        game.last_rotation = true;
+        assert_eq!(game.place().spin, SpinType::NONE);
+
+        // not rotated into place
+        let mut game = board_game(&T_SPIN_SINGLE);
+        put(&mut game, 'T', 2, T_IN_SLOT);
+        assert_eq!(game.place().spin, SpinType::NONE);
+    }
+
+    #[test]
+    fn tst_kick_upgrades_a_mini(){
+        let mut game = board_game(&T_SPIN_MINI);
+        put(&mut game, 'T', 0, T_ON_FLOOR);
+        game.last_rotation = true;
+        game.last_kick = TST_KICK;
+        let result = game.place();
+        assert_eq!((result.spin, result.score), (SpinType::FULL, 400));
+    }
+
+    #[test]
+    fn falling_after_the_rotation_cancels_the_spin(){
+        let above = [(0, 2), (1, 2), (2, 2), (1, 3)];
+        let mut game = board_game(&T_SPIN_MINI);
+        put(&mut game, 'T', 0, above);
+        game.last_rotation = true;
+        assert_eq!(game.hard_drop().unwrap().spin, SpinType::NONE);
+
+        let mut game = board_game(&T_SPIN_MINI);
+        put(&mut game, 'T', 0, above);
+        game.last_rotation = true;
+        while game.drop(){
+
+        }
+        assert_eq!(game.place().spin, SpinType::NONE);
+
+        // a hard drop that doesn't move the piece keeps it
+        let mut game = board_game(&T_SPIN_MINI);
+        put(&mut game, 'T', 0, T_ON_FLOOR);
+        game.last_rotation = true;
+        assert_eq!(game.hard_drop().unwrap().spin, SpinType::MINI);
+    }
+
+    #[test]
+    fn back_to_back_and_combo_scores(){
+        let mut rows = vec!["#........."];
+        rows.extend([TETRIS_WELL; 8]);
+        let mut game = board_game(&rows);
+        put(&mut game, 'I', 1, I_IN_WELL);
+        assert_eq!(game.place().score, 800);
+        // a piece without a clear ends the combo but not the back to back
+        put(&mut game, 'O', 0, [(1, 4), (2, 4), (1, 5), (2, 5)]);
+        assert_eq!(game.place().score, 0);
+        put(&mut game, 'I', 1, I_IN_WELL);
+        let result = game.place();
+        assert_eq!((result.b2b, result.combo), (1, 0));
+        assert_eq!(result.score, 1200);
+
+        // 50 more for every clear in a row
+        let mut game = board_game(&["#.........", "....######", "....######", "....######"]);
+        for score in [100, 150, 200]{
+            put(&mut game, 'I', 0, [(0, 0), (1, 0), (2, 0), (3, 0)]);
+            assert_eq!(game.place().score, score);
+        }
+    }
+
+    #[test]
+    fn perfect_clears(){
+        let mut game = board_game(&["....######"]);
+        put(&mut game, 'I', 0, [(0, 0), (1, 0), (2, 0), (3, 0)]);
+        let result = game.place();
+        assert!(result.perfect_clear);
+        assert_eq!(result.score, 100 + 800);
+        assert_eq!(result.attack, 10);
+
+        let mut game = board_game(&[TETRIS_WELL; 4]);
+        put(&mut game, 'I', 1, I_IN_WELL);
+        let result = game.place();
+        assert!(result.perfect_clear);
+        assert_eq!(result.score, 800 + 2000);
+        assert_eq!(result.attack, 4 + 10);
+
+        // anything left on the board is not a perfect clear
+        let mut game = board_game(&["#.........", "....######"]);
+        put(&mut game, 'I', 0, [(0, 0), (1, 0), (2, 0), (3, 0)]);
+        assert!(!game.place().perfect_clear);
+    }
+
+    #[test]
+    fn garbage_rises_after_a_lock_without_clear(){
+        let mut game = board_game(&["#.........", "....######"]);
+        game.receive_garbage(2, Some(7));
+        game.receive_garbage(1, Some(2));
+        // a line clear holds the garbage back
+        put(&mut game, 'I', 0, [(0, 0), (1, 0), (2, 0), (3, 0)]);
+        let result = game.place();
+        assert_eq!((result.attack, result.sent), (0, 0));
+        assert_eq!(game.garbage.batches().count(), 2);
+
+        put(&mut game, 'O', 0, [(4, 0), (5, 0), (4, 1), (5, 1)]);
+        game.rules.garbage_cap = 2;
+        game.place();
+        let bottom = game.board.len() - 1;
+        let hole = |y : usize| game.board[y].iter().position(|cell| *cell == 0);
+        assert_eq!((hole(bottom), hole(bottom - 1)), (Some(7), Some(7)));
+        // over the cap it waits for the next lock
+        assert_eq!(game.garbage.batches().map(|batch| batch.lines).sum::<u32>(), 1);
+    }
+
+    // moves a grounded T back and forth, `frames` after each move, until it locks or `moves` are done
+    fn stall(game : &mut Game, frames : u32, moves : u32) -> Option<u32>{
+        for i in 0..moves{
+            for _ in 0..frames{
+                if game.update().is_some(){
+                    return Some(i);
+                }
+            }
+            assert!(game.move_piece(if i % 2 == 0 {1} else {-1}));
+        }
+        return None;
+    }
+
+    fn grounded_t(lock_reset : LockReset) -> Game{
+        let mut rules = Rules::new();
+        rules.lock_reset = lock_reset;
+        let mut game = board_game_with(rules, &[]);
+        put(&mut game, 'T', 0, [(4, 0), (5, 0), (6, 0), (5, 1)]);
+        return game;
+    }
+
+    #[test]
+    fn move_reset_stops_at_the_limit(){
+        let mut game = grounded_t(LockReset::MOVE_RESET);
+        let (delay, limit) = (game.rules.lock_delay, game.rules.lock_reset_limit);
+        // every move resets the lock delay until the limit, the move after that doesn't
+        assert_eq!(stall(&mut game, delay - 1, limit + 5), Some(limit + 1));
+        assert_eq!(game.board[game.height - 1][4..7].iter().filter(|cell| **cell != 0).count(), 3);
+    }
+
+    #[test]
+    fn a_new_lowest_row_gives_the_resets_back(){
+        let mut game = board_game(&[]);
+        let limit = game.rules.lock_reset_limit;
+        let lowest = game.lowest_row;
+        game.lock_resets = limit;
+        assert!(game.drop());
+        assert_eq!((game.lowest_row, game.lock_resets), (lowest + 1, 0));
+    }
+
+    #[test]
+    fn infinite_and_step_reset(){
+        let mut game = grounded_t(LockReset::INFINITE);
+        let delay = game.rules.lock_delay;
+        assert_eq!(stall(&mut game, delay - 1, 100), None);
+        // step reset ignores moves, only falling resets the lock delay
+        let mut game = grounded_t(LockReset::STEP_RESET);
+        assert_eq!(stall(&mut game, delay - 1, 100), Some(1));
+    }
+
+    // rows from the bottom, the 20 lowest are the visible ones
+    const SKYLINE : i8 = 20;
+
+    #[test]
+    fn block_out_when_the_next_piece_overlaps(){
+        let mut game = board_game(&[]);
+        put(&mut game, 'I', 0, [(0, 0), (1, 0), (2, 0), (3, 0)]);
+        // the stack reaches into the hidden rows where pieces spawn
+        for row in &mut game.board[BUFFER_HEIGHT - 2..BUFFER_HEIGHT + 1]{
+            row[3..7].fill(GARBAGE);
+        }
+        game.hard_drop();
+        assert_eq!(game.top_out(), Some(TopOut::BLOCK_OUT));
+        assert!(game.update().is_none());
+        assert!(game.hard_drop().is_none());
+    }
+
+    #[test]
+    fn lock_out_above_the_skyline(){
+        let mut game = board_game(&[]);
+        put(&mut game, 'T', 0, [(3, SKYLINE), (4, SKYLINE), (5, SKYLINE), (4, SKYLINE + 1)]);
+        let result = game.place();
+        assert_eq!(result.top_out, Some(TopOut::LOCK_OUT));
+        assert_eq!(game.top_out(), Some(TopOut::LOCK_OUT));
+    }
+
+    #[test]
+    fn a_piece_partly_above_the_skyline_keeps_playing(){
+        let mut game = board_game(&["#########."; 19]);
+        put(&mut game, 'T', 0, [(0, SKYLINE - 1), (1, SKYLINE - 1), (2, SKYLINE - 1), (1, SKYLINE)]);
+        let result = game.hard_drop().unwrap();
+        assert_eq!(result.top_out, None);
+        assert_eq!(game.top_out(), None);
+        assert!(game.piece_active());
+        // the cell above the skyline stays on the board
+        assert_eq!(game.board[BUFFER_HEIGHT - 1][1], PIECES[piece_index('T').unwrap()].index);
+    }
+
+    fn last_frame_of_blitz() -> Game{
+        let mut rules = Rules::new();
+        rules.mode = Mode::BLITZ;
+        let mut game = Game::new(rules, 1);
+        game.frames = BLITZ_FRAMES - 1;
+        return game;
+    }
+
+    #[test]
+    fn lock_on_the_last_frame_counts(){
+        let mut game = last_frame_of_blitz();
+        game.sonic_drop();
+        game.lock_timer = game.rules.lock_delay - 1;
+        let result = game.update().unwrap();
+        assert_eq!(game.time_left(), Some(0));
+        assert!(result.top_out.is_none());
+        assert!(game.time_up());
+        assert!(!game.piece_active());
+        // no new piece, nothing changes after the end
+        let board = game.board.clone();
+        assert!(game.update().is_none());
+        assert_eq!(game.board, board);
+    }
+
+    #[test]
+    fn piece_in_play_locks_after_the_time_is_out(){
+        let mut game = last_frame_of_blitz();
+        assert!(game.update().is_none());
+        assert_eq!(game.time_left(), Some(0));
+        assert!(!game.time_up());
+        assert!(game.piece_active());
+        // it can still move and fall, but not be swapped for a new piece
+        assert!(!game.hold_piece());
+        let score = game.score;
+        game.soft_drop();
+        let result = game.hard_drop().unwrap();
+        assert!(result.score > 0);
+        assert_eq!(game.score, score + result.score);
+        assert!(game.time_up());
+        assert!(game.hard_drop().is_none());
+    }
+}
@@ -0,0 +1,560 @@
+// the engine's original style: SCREAMING_CASE enum variants, camelCase in places and explicit returns
+#![allow(non_camel_case_types, non_snake_case)]
+#![allow(clippy::needless_return, clippy::upper_case_acronyms)]
+
+use std::{cmp::min, collections::HashMap};
+
+use crate::tetris_engine::{Game, LockResult, SoftDropFactor, SpawnInput};
+
+/// inputs kept during ARE and line clear delay, more presses are dropped
+pub const BUFFER_LIMIT : usize = 8;
+
+/// what a key does, the frontend maps its keys to these
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Action {NONE, LEFT, RIGHT, DOWN, ROTATE_C, ROTATE_A, ROTATE_H, DROP, HOLD, UNDO, REDO}
+
+impl Action {
+    pub fn from_name(name : &str) -> Option<Self>{
+        return match name {
+            "left" => Some(Action::LEFT),
+            "right" => Some(Action::RIGHT),
+            "down" => Some(Action::DOWN),
+            "cw" => Some(Action::ROTATE_C),
+            "ccw" => Some(Action::ROTATE_A),
+            "180" => Some(Action::ROTATE_H),
+            "drop" => Some(Action::DROP),
+            "hold" => Some(Action::HOLD),
+            "undo" => Some(Action::UNDO),
+            "redo" => Some(Action::REDO),
+            _ => None,
+        };
+    }
+
+    pub fn name(&self) -> &'static str{
+        return match self {
+            Action::NONE => "none",
+            Action::LEFT => "left",
+            Action::RIGHT => "right",
+            Action::DOWN => "down",
+            Action::ROTATE_C => "cw",
+            Action::ROTATE_A => "ccw",
+            Action::ROTATE_H => "180",
+            Action::DROP => "drop",
+            Action::HOLD => "hold",
+            Action::UNDO => "undo",
+            Action::REDO => "redo",
+        };
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Direction {LEFT, RIGHT, NONE}
+
+struct ActionMap {
+    map: HashMap<Action, bool>,
+}
+
+impl ActionMap {
+    fn new() -> Self {
+        let mut map = HashMap::new();
+        // Esempio di inizializzazione
+        map.insert(Action::LEFT, false);
+        map.insert(Action::RIGHT, false);
+        map.insert(Action::DOWN, false);
+        map.insert(Action::DROP, false);
+        map.insert(Action::ROTATE_C, false);
+        map.insert(Action::ROTATE_A, false);
+        map.insert(Action::ROTATE_H, false);
+        map.insert(Action::HOLD, false);
+
+
+        Self { map }
+    }
+
+    fn get(&self, key: &Action) -> Option<&bool> {
+        self.map.get(key)
+    }
+
+    fn set(&mut self, key: Action, value: bool) {
+        self.map.insert(key, value);
+    }
+}
+
+/// what happens when left and right are both held
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DirectionPolicy {
+    // the last pressed direction wins, releasing it goes back to the other one
+    LAST_PRESSED,
+    // the piece stops until one of them is released
+    CANCEL,
+}
+
+impl DirectionPolicy {
+    pub fn from_name(name : &str) -> Option<Self>{
+        return match name {
+            "last" => Some(DirectionPolicy::LAST_PRESSED),
+            "cancel" => Some(DirectionPolicy::CANCEL),
+            _ => None,
+        };
+    }
+
+    pub fn name(&self) -> &'static str{
+        return match self {
+            DirectionPolicy::LAST_PRESSED => "last",
+            DirectionPolicy::CANCEL => "cancel",
+        };
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Handling{
+    /// frames a direction has to be held before autoshift starts
+    pub das_delay: u32,
+    /// frames between autoshift moves
+    pub arr: u32,
+    pub sdf : SoftDropFactor,
+    /// initial rotation and initial hold, applied when a piece spawns with the key held
+    pub irs : bool,
+    pub ihs : bool,
+    /// keep the DAS charge when a piece locks instead of charging again for the next one
+    pub das_carry : bool,
+    /// frames autoshift waits after a rotation or hard drop
+    pub dcd : u32,
+    pub direction_policy : DirectionPolicy,
+}
+
+impl Default for Handling {
+    fn default() -> Self{
+        return Handling::new();
+    }
+}
+
+impl Handling {
+    pub fn new() -> Self{
+        Self{
+            das_delay: 10,
+            arr: 1,
+            sdf : SoftDropFactor::FACTOR(30),
+            irs : false,
+            ihs : false,
+            das_carry : true,
+            dcd : 0,
+            direction_policy : DirectionPolicy::LAST_PRESSED,
+        }
+    }
+}
+
+pub struct UserControl{
+    action_map: ActionMap,
+    pub(crate) handling : Handling,
+
+    pub(crate) frame : u32,
+
+    
+    direction : Direction,
+    dropping : bool,
+    pub(crate) hold: u32,
+    pub(crate) arr : u32,
+
+    // inputs pressed during ARE or line clear delay, the ones after a drop are for the piece after
+    pub(crate) buffered : Vec<Action>,
+}
+
+impl UserControl{
+    pub fn new(handling : Handling) -> Self{
+        Self{
+            action_map : ActionMap::new(),
+            handling,
+            frame : 0,
+        
+            dropping : false,
+            hold: 0,
+            direction : Direction::NONE,
+            arr : 0,
+            buffered : Vec::new(),
+        }
+    }
+
+    /// a key of the frontend was pressed or released
+    pub fn action(&mut self, game : &mut Game, action : Action, pressed : bool) -> Option<LockResult>{
+        // undo and redo go to the History, not to the piece
+        if matches!(action, Action::NONE | Action::UNDO | Action::REDO){
+            return None;
+        }
+        if pressed{ 
+            self.action_map.set(action, true);  
+
+            // rotations, holds and hard drops pressed while there is no piece wait for the next one
+            if !game.piece_active() && matches!(action, Action::ROTATE_C | Action::ROTATE_A | Action::ROTATE_H | Action::DROP | Action::HOLD){
+                if self.buffered.len() < BUFFER_LIMIT{
+                    self.buffered.push(action);
+                }
+                return None;
+            }
+            return self.press(game, action);
+        }else{
+            self.action_map.set(action, false);  
+            match action {
+                Action::LEFT | Action::RIGHT => {
+                    // the other direction takes over if it is still held
+                    let other = if action == Action::LEFT {Action::RIGHT} else {Action::LEFT};
+                    if self.is_held(other){
+                        self.set_direction(game, if other == Action::LEFT {Direction::LEFT} else {Direction::RIGHT});
+                    }else{
+                        self.stop_direction();
+                    }
+                },
+                Action::DOWN => {
+                    self.dropping = false;
+                },
+                _ => (),       
+            }  
+        }
+        return None;
+    }
+
+    fn set_direction(&mut self, game : &mut Game, direction : Direction){
+        if self.direction != direction{
+            self.hold = 0;
+            self.arr = self.frame;
+            self.direction = direction;
+            game.move_piece(if Direction::LEFT == direction {-1} else {1});
+        }
+    }
+
+    fn stop_direction(&mut self){
+        self.hold = 0;
+        self.arr = self.frame;
+        self.direction = Direction::NONE;
+    }
+
+    // DAS cut delay, a charged DAS waits dcd frames before shifting again,
+    // the charge goes up by one before every shift check
+    fn cut_das(&mut self){
+        if self.handling.dcd > 0{
+            self.hold = min(self.hold, self.handling.das_delay.saturating_sub(self.handling.dcd + 1));
+        }
+    }
+
+    fn piece_locked(&mut self){
+        if !self.handling.das_carry{
+            self.hold = 0;
+        }
+    }
+
+    fn is_held(&self, action : Action) -> bool{
+        return *self.action_map.get(&action).unwrap_or(&false);
+    }
+
+    // buffered inputs up to the first drop, the ones for the next piece
+    fn next_piece_buffer(&self) -> &[Action]{
+        let end = self.buffered.iter().position(|action| *action == Action::DROP).map_or(self.buffered.len(), |i| i + 1);
+        return &self.buffered[..end];
+    }
+
+    // rotations and holds held down or buffered are applied to the next piece that spawns
+    fn spawn_input(&self) -> SpawnInput{
+        let pressed = |action : Action| self.is_held(action) || self.next_piece_buffer().contains(&action);
+        let mut input = SpawnInput::default();
+        if self.handling.irs{
+            input.rotation = if pressed(Action::ROTATE_H) {2} else if pressed(Action::ROTATE_C) {1} else if pressed(Action::ROTATE_A) {3} else {0};
+        }
+        input.hold = self.handling.ihs && pressed(Action::HOLD);
+        return input;
+    }
+
+    fn press(&mut self, game : &mut Game, action : Action) -> Option<LockResult>{
+        game.set_spawn_input(self.spawn_input());
+        match action {
+            Action::LEFT | Action::RIGHT => {
+                let other = if action == Action::LEFT {Action::RIGHT} else {Action::LEFT};
+                if self.handling.direction_policy == DirectionPolicy::CANCEL && self.is_held(other){
+                    self.stop_direction();
+                }else{
+                    self.set_direction(game, if action == Action::LEFT {Direction::LEFT} else {Direction::RIGHT});
+                }
+            },
+            Action::ROTATE_C | Action::ROTATE_A | Action::ROTATE_H => {
+                game.rotate(
+                    if action == Action::ROTATE_C {1} else if action == Action::ROTATE_A {3} else {2}
+                );
+                self.cut_das();
+            },
+            Action::DROP => {
+                let result = game.hard_drop();
+                self.cut_das();
+                if result.is_some(){
+                    self.piece_locked();
+                }
+                return result;
+            },
+            Action::DOWN => {
+                self.dropping = true;
+            },
+            Action::HOLD => {
+                game.hold_piece();
+            },
+            _ => (),       
+        }
+        return None;
+    }
+
+    pub fn update(&mut self, game : &mut Game) -> Option<LockResult>{
+        self.frame += 1;
+
+        self.hold += 1;
+
+        game.set_spawn_input(self.spawn_input());
+        game.set_soft_drop(if self.dropping {Some(self.handling.sdf)} else {None});
+        let mut result = game.update();
+        if result.is_some(){
+            self.piece_locked();
+        }
+        if game.top_out().is_some(){
+            return result;
+        }
+
+        if game.piece_active() && !self.buffered.is_empty(){
+            let end = self.next_piece_buffer().len();
+            // what comes after a buffered drop stays buffered for the piece after it,
+            // it is seen by IRS and IHS when the drop spawns that piece
+            let rest = self.buffered.split_off(end);
+            let mut buffered = std::mem::replace(&mut self.buffered, rest);
+            // buffered rotations and holds were already used by IRS and IHS on spawn
+            if self.handling.irs{
+                buffered.retain(|action| !matches!(action, Action::ROTATE_C | Action::ROTATE_A | Action::ROTATE_H));
+            }
+            if self.handling.ihs{
+                buffered.retain(|action| *action != Action::HOLD);
+            }
+            for action in buffered{
+                if let Some(lock) = self.press(game, action){
+                    result = Some(lock);
+                }
+            }
+            if game.top_out().is_some(){
+                return result;
+            }
+        }
+
+        match self.direction {
+            Direction::LEFT | Direction::RIGHT if self.hold >= self.handling.das_delay && self.frame - self.arr >= self.handling.arr => {
+                //println!("{} {} - {} {}", self._hold, self.das_delay, self.frame, self._arr);
+                game.move_piece(if self.direction == Direction::LEFT {-1} else {1});
+
+                self.arr = self.frame;
+            },
+            _ => (),
+        }
+        return result;
+    }
+
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tetris_engine::Rules;
+
+    fn tap(controls : &mut UserControl, game : &mut Game, action : Action){
+        controls.action(game, action, true);
+        controls.action(game, action, false);
+    }
+
+    fn buffered_game(irs : bool) -> (Game, UserControl){
+        let mut rules = Rules::new();
+        rules.are = 10;
+        let mut game = Game::new(rules, 1);
+        let mut handling = Handling::new();
+        handling.irs = irs;
+        let mut controls = UserControl::new(handling);
+        tap(&mut controls, &mut game, Action::DROP);
+        assert!(!game.piece_active());
+        // during ARE: a drop for the next piece, then a rotation for the one after
+        tap(&mut controls, &mut game, Action::DROP);
+        tap(&mut controls, &mut game, Action::ROTATE_C);
+        return (game, controls);
+    }
+
+    #[test]
+    fn rotation_after_buffered_drop_waits_for_the_next_spawn(){
+        let (mut game, mut controls) = buffered_game(true);
+        let mut locks = 0;
+        for _ in 0..9{
+            locks += controls.update(&mut game).is_some() as u32;
+        }
+        // the second piece spawned unrotated and was dropped, the rotation is still buffered
+        assert_eq!(locks, 0);
+        controls.update(&mut game);
+        assert_eq!(game.board.iter().flatten().filter(|cell| **cell != 0).count(), 8);
+        assert_eq!(controls.buffered, vec![Action::ROTATE_C]);
+        for _ in 0..10{
+            controls.update(&mut game);
+        }
+        // IRS used it on the third piece
+        assert!(game.piece_active());
+        assert_eq!(game.current_rotation, 1);
+        assert!(controls.buffered.is_empty());
+    }
+
+    #[test]
+    fn rotation_after_buffered_drop_without_irs(){
+        let (mut game, mut controls) = buffered_game(false);
+        for _ in 0..21{
+            controls.update(&mut game);
+        }
+        assert!(game.piece_active());
+        assert_eq!(game.current_rotation, 1);
+        assert!(controls.buffered.is_empty());
+    }
+
+    fn waiting_game(handling : Handling) -> (Game, UserControl){
+        let mut rules = Rules::new();
+        rules.are = 10;
+        let mut game = Game::new(rules, 1);
+        let mut controls = UserControl::new(handling);
+        tap(&mut controls, &mut game, Action::DROP);
+        assert!(!game.piece_active());
+        return (game, controls);
+    }
+
+    fn spawn(controls : &mut UserControl, game : &mut Game){
+        while !game.piece_active(){
+            controls.update(game);
+        }
+    }
+
+    #[test]
+    fn irs_rotates_the_piece_as_it_spawns(){
+        let mut handling = Handling::new();
+        handling.irs = true;
+        let (mut game, mut controls) = waiting_game(handling);
+        controls.action(&mut game, Action::ROTATE_A, true);
+        spawn(&mut controls, &mut game);
+        // once, the buffered press isn't applied again
+        assert_eq!(game.current_rotation, 3);
+        assert!(controls.buffered.is_empty());
+
+        // still held when the next piece spawns
+        tap(&mut controls, &mut game, Action::DROP);
+        spawn(&mut controls, &mut game);
+        assert_eq!(game.current_rotation, 3);
+        controls.action(&mut game, Action::ROTATE_A, false);
+        tap(&mut controls, &mut game, Action::DROP);
+        spawn(&mut controls, &mut game);
+        assert_eq!(game.current_rotation, 0);
+    }
+
+    #[test]
+    fn ihs_holds_the_piece_as_it_spawns(){
+        let mut handling = Handling::new();
+        handling.ihs = true;
+        let (mut game, mut controls) = waiting_game(handling);
+        let nexts : Vec<char> = game.get_nexts().iter().map(|piece| piece.name).collect();
+        controls.action(&mut game, Action::HOLD, true);
+        spawn(&mut controls, &mut game);
+        assert_eq!(game.hold_piece.map(|piece| piece.name), Some(nexts[0]));
+        assert_eq!(game.current_piece.name, nexts[1]);
+        // one hold per piece, the buffered press doesn't swap them back
+        assert!(controls.buffered.is_empty());
+        controls.update(&mut game);
+        assert_eq!(game.current_piece.name, nexts[1]);
+    }
+
+    #[test]
+    fn held_keys_do_nothing_at_spawn_without_irs_and_ihs(){
+        let mut rules = Rules::new();
+        rules.are = 0;
+        let mut game = Game::new(rules, 1);
+        let mut controls = UserControl::new(Handling::new());
+        let next = game.get_nexts()[0].name;
+        controls.action(&mut game, Action::ROTATE_C, true);
+        tap(&mut controls, &mut game, Action::DROP);
+        assert_eq!((game.current_piece.name, game.current_rotation), (next, 0));
+    }
+
+    fn handling_game(handling : Handling) -> (Game, UserControl){
+        return (Game::new(Rules::new(), 1), UserControl::new(handling));
+    }
+
+    fn updates(controls : &mut UserControl, game : &mut Game, frames : u32){
+        for _ in 0..frames{
+            controls.update(game);
+        }
+    }
+
+    #[test]
+    fn das_carry_keeps_the_charge_for_the_next_piece(){
+        for das_carry in [true, false]{
+            let mut handling = Handling::new();
+            handling.das_carry = das_carry;
+            let das = handling.das_delay;
+            let (mut game, mut controls) = handling_game(handling);
+            controls.action(&mut game, Action::RIGHT, true);
+            updates(&mut controls, &mut game, das);
+            tap(&mut controls, &mut game, Action::DROP);
+            let x = game.current_position.0;
+            controls.update(&mut game);
+            // a carried charge shifts the new piece on its first frame
+            assert_eq!(game.current_position.0 > x, das_carry);
+            updates(&mut controls, &mut game, das);
+            assert!(game.current_position.0 > x);
+        }
+    }
+
+    #[test]
+    fn dcd_delays_autoshift_after_a_rotation(){
+        for dcd in [0, 4]{
+            let mut handling = Handling::new();
+            handling.dcd = dcd;
+            let das = handling.das_delay;
+            let (mut game, mut controls) = handling_game(handling);
+            controls.action(&mut game, Action::RIGHT, true);
+            updates(&mut controls, &mut game, das);
+            tap(&mut controls, &mut game, Action::ROTATE_C);
+            let x = game.current_position.0;
+            updates(&mut controls, &mut game, dcd);
+            assert_eq!(game.current_position.0, x);
+            controls.update(&mut game);
+            assert_eq!(game.current_position.0, x + 1);
+        }
+    }
+
+    #[test]
+    fn direction_policies(){
+        let das = Handling::new().das_delay;
+        let (mut game, mut controls) = handling_game(Handling::new());
+        let x = game.current_position.0;
+        controls.action(&mut game, Action::LEFT, true);
+        controls.action(&mut game, Action::RIGHT, true);
+        assert_eq!(game.current_position.0, x);
+        // the last pressed direction wins, the other one takes over when it is released
+        updates(&mut controls, &mut game, das);
+        assert_eq!(game.current_position.0, x + 1);
+        controls.action(&mut game, Action::RIGHT, false);
+        assert_eq!(game.current_position.0, x);
+
+        let mut handling = Handling::new();
+        handling.direction_policy = DirectionPolicy::CANCEL;
+        let (mut game, mut controls) = handling_game(handling);
+        controls.action(&mut game, Action::LEFT, true);
+        controls.action(&mut game, Action::RIGHT, true);
+        // both held, the piece stops
+        updates(&mut controls, &mut game, das);
+        assert_eq!(game.current_position.0, x - 1);
+        controls.action(&mut game, Action::RIGHT, false);
+        assert_eq!(game.current_position.0, x - 2);
+    }
+
+    #[test]
+    fn buffer_is_capped(){
+        let mut rules = Rules::new();
+        rules.are = 10;
+        let mut game = Game::new(rules, 1);
+        let mut controls = UserControl::new(Handling::new());
+        tap(&mut controls, &mut game, Action::DROP);
+        for _ in 0..BUFFER_LIMIT * 2{
+            tap(&mut controls, &mut game, Action::ROTATE_A);
+        }
+        assert_eq!(controls.buffered.len(), BUFFER_LIMIT);
+    }
+}